@@ -1,17 +1,16 @@
 use crate::adc::adc_sample::ADCSample;
 use crate::adc::{adc_service, ADCChannel, ADCConfig, ADCData, ADCPulseInError, ADCReadError};
 use crate::iowarrior::Peripheral;
-use crate::iowarrior::{peripheral_service, IOWarriorData, IOWarriorMutData};
+use crate::iowarrior::{iowarrior_service, peripheral_service, IOWarriorData, IOWarriorMutData};
 use embedded_hal::digital::PinState;
-use std::cell::RefCell;
 use std::fmt;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 #[derive(Debug)]
 pub struct ADC {
-    pub(crate) data: Rc<IOWarriorData>,
-    pub(crate) mut_data_refcell: Rc<RefCell<IOWarriorMutData>>,
+    pub(crate) data: Arc<IOWarriorData>,
+    pub(crate) mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
     pub(crate) adc_data: ADCData,
 }
 
@@ -20,7 +19,7 @@ impl Drop for ADC {
     fn drop(&mut self) {
         peripheral_service::disable_peripheral(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             Peripheral::ADC,
         );
     }
@@ -50,11 +49,24 @@ impl ADC {
 
     #[inline]
     pub fn read(&mut self, buffer: &mut [Option<ADCSample>]) -> Result<(), ADCReadError> {
-        let mut mut_data = self.mut_data_refcell.borrow_mut();
+        let mut mut_data = self.mut_data_mutex.lock().unwrap();
 
         adc_service::read_samples(&self.data, &mut mut_data, &self.adc_data, buffer)
     }
 
+    #[inline]
+    pub fn read_with_timeout(
+        &mut self,
+        buffer: &mut [Option<ADCSample>],
+        timeout: Duration,
+    ) -> Result<(), ADCReadError> {
+        let mut mut_data = self.mut_data_mutex.lock().unwrap();
+
+        iowarrior_service::with_read_timeout(&mut mut_data, timeout, |mut_data| {
+            adc_service::read_samples(&self.data, mut_data, &self.adc_data, buffer)
+        })
+    }
+
     #[inline]
     pub fn pulse_in(
         &mut self,
@@ -62,7 +74,7 @@ impl ADC {
         pin_state: PinState,
         timeout: Duration,
     ) -> Result<Duration, ADCPulseInError> {
-        let mut mut_data = self.mut_data_refcell.borrow_mut();
+        let mut mut_data = self.mut_data_mutex.lock().unwrap();
 
         adc_service::pulse_in(
             &self.data,
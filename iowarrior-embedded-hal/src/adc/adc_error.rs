@@ -1,5 +1,5 @@
 use crate::adc::ADCChannel;
-use hidapi::HidError;
+use crate::communication::TransportError;
 use thiserror::Error;
 
 #[non_exhaustive]
@@ -7,8 +7,14 @@ use thiserror::Error;
 pub enum ADCReadError {
     #[error("Sampling interrupted, a packet was lost.")]
     PacketLoss,
-    #[error("USB HID error.")]
-    ErrorUSB(HidError),
+    #[error("USB transport error.")]
+    ErrorUSB(#[source] TransportError),
+    #[error("IOWarrior disconnected.")]
+    Disconnected,
+    #[error("Timeout while waiting for IOWarrior.")]
+    Timeout,
+    #[error("IOWarrior operation cancelled.")]
+    Cancelled,
 }
 
 #[non_exhaustive]
@@ -16,10 +22,27 @@ pub enum ADCReadError {
 pub enum ADCPulseInError {
     #[error("Sampling interrupted, a packet was lost.")]
     PacketLoss,
-    #[error("USB HID error.")]
-    ErrorUSB(HidError),
+    #[error("USB transport error.")]
+    ErrorUSB(#[source] TransportError),
+    #[error("IOWarrior disconnected.")]
+    Disconnected,
+    #[error("Timeout while waiting for IOWarrior.")]
+    Timeout,
+    #[error("IOWarrior operation cancelled.")]
+    Cancelled,
     #[error("Timeout while waiting for pulse.")]
     PulseTimeout,
     #[error("ADC channel {0} is not enabled in current config.")]
     InvalidChannel(ADCChannel),
 }
+
+impl From<TransportError> for ADCReadError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Disconnected => ADCReadError::Disconnected,
+            TransportError::Timeout => ADCReadError::Timeout,
+            TransportError::Cancelled => ADCReadError::Cancelled,
+            error => ADCReadError::ErrorUSB(error),
+        }
+    }
+}
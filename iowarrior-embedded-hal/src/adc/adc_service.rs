@@ -3,28 +3,26 @@ use crate::adc::{
     ADCChannel, ADCConfig, ADCData, ADCPulseInError, ADCReadError, IOW28IOW100ADCConfig,
     IOW56ADCConfig, IOWarriorADCType, SampleRate1ch, SampleRate2ch, SampleRate4ch, ADC,
 };
-use crate::communication::communication_service;
+use crate::communication::TransportError;
 use crate::iowarrior::{
-    peripheral_service, IOWarriorData, IOWarriorMutData, Peripheral, PeripheralSetupError, Pipe,
-    ReportId,
+    iowarrior_service, peripheral_service, IOWarriorData, IOWarriorMutData, Peripheral,
+    PeripheralSetupError, Pipe, ReportId,
 };
 use crate::{iowarrior::IOWarriorType, pin};
 use embedded_hal::digital::PinState;
-use hidapi::HidError;
-use std::cell::{RefCell, RefMut};
 use std::ops::Not;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Duration;
 
 pub fn new(
-    data: &Rc<IOWarriorData>,
-    mut_data_refcell: &Rc<RefCell<IOWarriorMutData>>,
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
     adc_config: ADCConfig,
 ) -> Result<ADC, PeripheralSetupError> {
     match get_adc_type(&data) {
         None => Err(PeripheralSetupError::NotSupported),
         Some(adc_type) => {
-            let mut mut_data = mut_data_refcell.borrow_mut();
+            let mut mut_data = mut_data_mutex.lock().unwrap();
 
             let resolution_bits = get_resolution_bits(adc_type);
             let report_channel_count = get_report_channel_count(adc_type, adc_config);
@@ -51,23 +49,22 @@ pub fn new(
                 &adc_pins,
             )?;
 
-            send_enable_adc(&data, &mut mut_data, &adc_data)
-                .map_err(|x| PeripheralSetupError::ErrorUSB(x))?;
+            send_enable_adc(&data, &mut mut_data, &adc_data).map_err(PeripheralSetupError::from)?;
 
             peripheral_service::post_enable(&mut mut_data, &adc_pins, Peripheral::ADC);
 
-            let adc_data_refcell = Rc::new(RefCell::new(adc_data));
+            let adc_data_mutex = Arc::new(Mutex::new(adc_data));
 
             Ok(ADC {
                 data: data.clone(),
-                mut_data_refcell: mut_data_refcell.clone(),
+                mut_data_mutex: mut_data_mutex.clone(),
                 adc_data,
             })
         }
     }
 }
 
-fn get_adc_type(data: &Rc<IOWarriorData>) -> Option<IOWarriorADCType> {
+fn get_adc_type(data: &Arc<IOWarriorData>) -> Option<IOWarriorADCType> {
     match data.device_type {
         IOWarriorType::IOWarrior28 => Some(IOWarriorADCType::IOWarrior28),
         IOWarriorType::IOWarrior100 => Some(IOWarriorADCType::IOWarrior100),
@@ -187,8 +184,36 @@ fn get_sampling_frequency_hz(adc_type: IOWarriorADCType, adc_config: ADCConfig)
     }
 }
 
+pub fn get_alternate_pins(device_type: IOWarriorType) -> Vec<u8> {
+    let adc_type = match device_type {
+        IOWarriorType::IOWarrior28 => IOWarriorADCType::IOWarrior28,
+        IOWarriorType::IOWarrior56 => IOWarriorADCType::IOWarrior56,
+        IOWarriorType::IOWarrior100 => IOWarriorADCType::IOWarrior100,
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior56Dongle
+        | IOWarriorType::IOWarrior28Dongle
+        | IOWarriorType::IOWarrior28L => return vec![],
+    };
+
+    get_adc_channel_pins(adc_type)
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
 fn get_adc_pins(adc_data: &ADCData) -> Vec<u8> {
-    let pins = match adc_data.adc_type {
+    get_adc_channel_pins(adc_data.adc_type)
+        .into_iter()
+        .take(adc_data.highest_enabled_channel.get_value() as usize)
+        .filter(|x| x.is_some())
+        .map(|x| x.unwrap())
+        .collect()
+}
+
+fn get_adc_channel_pins(adc_type: IOWarriorADCType) -> [Option<u8>; 8] {
+    match adc_type {
         IOWarriorADCType::IOWarrior28 => [
             Some(pin!(1, 0)),
             Some(pin!(1, 1)),
@@ -219,20 +244,14 @@ fn get_adc_pins(adc_data: &ADCData) -> Vec<u8> {
             None,
             None,
         ],
-    };
-
-    pins.into_iter()
-        .take(adc_data.highest_enabled_channel.get_value() as usize)
-        .filter(|x| x.is_some())
-        .map(|x| x.unwrap())
-        .collect()
+    }
 }
 
 fn send_enable_adc(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     adc_data: &ADCData,
-) -> Result<(), HidError> {
+) -> Result<(), TransportError> {
     let mut report = data.create_report(Pipe::ADCMode);
 
     report.buffer[0] = ReportId::AdcSetup.get_value();
@@ -254,12 +273,16 @@ fn send_enable_adc(
         }
     }
 
-    communication_service::write_report(&mut mut_data.communication_data, &report)
+    iowarrior_service::write_report(data, mut_data, &report)?;
+
+    peripheral_service::store_peripheral_setup(mut_data, Peripheral::ADC, vec![report]);
+
+    Ok(())
 }
 
 pub fn read_samples(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     adc_data: &ADCData,
     buffer: &mut [Option<ADCSample>],
 ) -> Result<(), ADCReadError> {
@@ -273,8 +296,8 @@ pub fn read_samples(
 }
 
 pub fn pulse_in(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     adc_data: &ADCData,
     channel: ADCChannel,
     pin_state: PinState,
@@ -293,6 +316,9 @@ pub fn pulse_in(
             |x| match x {
                 ADCReadError::PacketLoss => ADCPulseInError::PacketLoss,
                 ADCReadError::ErrorUSB(y) => ADCPulseInError::ErrorUSB(y),
+                ADCReadError::Disconnected => ADCPulseInError::Disconnected,
+                ADCReadError::Timeout => ADCPulseInError::Timeout,
+                ADCReadError::Cancelled => ADCPulseInError::Cancelled,
             },
         )?;
 
@@ -367,17 +393,14 @@ fn get_pin_state(adc_sample: &ADCSample, adc_data: &ADCData) -> PinState {
 }
 
 fn read_samples_report(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     adc_data: &ADCData,
     buffer: &mut [Option<ADCSample>],
     last_packet: &mut Option<u8>,
 ) -> Result<(), ADCReadError> {
-    let report = communication_service::read_report(
-        &mut mut_data.communication_data,
-        data.create_report(Pipe::ADCMode),
-    )
-    .map_err(|x| ADCReadError::ErrorUSB(x))?;
+    let report = iowarrior_service::read_report(data, mut_data, data.create_report(Pipe::ADCMode))
+        .map_err(ADCReadError::from)?;
 
     update_packet_number(last_packet, report.buffer[1])?;
 
@@ -10,3 +10,5 @@ pub use self::adc_config::*;
 pub(crate) use self::adc_data::*;
 pub use self::adc_error::*;
 pub use self::adc_sample::*;
+
+static_assertions::assert_impl_all!(ADC: Send, Sync);
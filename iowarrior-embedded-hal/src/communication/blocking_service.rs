@@ -0,0 +1,100 @@
+use crate::communication::{BlockingTask, BlockingTaskState, TransportError};
+use std::collections::VecDeque;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+type BlockingJob = Box<dyn FnOnce() + Send>;
+
+// Async operations run on a shared pool of worker threads, idle workers exit after a while.
+static BLOCKING_POOL: Mutex<BlockingPool> = Mutex::new(BlockingPool {
+    jobs: VecDeque::new(),
+    idle_workers: 0,
+});
+
+static BLOCKING_CONDVAR: Condvar = Condvar::new();
+
+const WORKER_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct BlockingPool {
+    jobs: VecDeque<BlockingJob>,
+    idle_workers: usize,
+}
+
+pub fn spawn_blocking<T, F>(function: F) -> Result<BlockingTask<T>, TransportError>
+where
+    T: Send + 'static,
+    F: FnOnce(&AtomicBool) -> T + Send + 'static,
+{
+    let state_mutex = Arc::new(Mutex::new(BlockingTaskState {
+        result: None,
+        waker: None,
+    }));
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let job: BlockingJob = {
+        let state_mutex = state_mutex.clone();
+        let cancelled = cancelled.clone();
+
+        Box::new(move || {
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| function(&cancelled)));
+
+            let mut state = state_mutex.lock().unwrap();
+
+            state.result = Some(result);
+
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        })
+    };
+
+    let mut blocking_pool = BLOCKING_POOL.lock().unwrap();
+
+    blocking_pool.jobs.push_back(job);
+
+    if blocking_pool.jobs.len() <= blocking_pool.idle_workers {
+        BLOCKING_CONDVAR.notify_one();
+    } else if let Err(error) = thread::Builder::new()
+        .name("iowarrior-blocking".to_owned())
+        .spawn(run_worker)
+    {
+        blocking_pool.jobs.pop_back();
+
+        return Err(TransportError::IO(error));
+    }
+
+    Ok(BlockingTask {
+        state_mutex,
+        cancelled,
+    })
+}
+
+fn run_worker() {
+    let mut blocking_pool = BLOCKING_POOL.lock().unwrap();
+
+    loop {
+        if let Some(job) = blocking_pool.jobs.pop_front() {
+            drop(blocking_pool);
+            job();
+            blocking_pool = BLOCKING_POOL.lock().unwrap();
+            continue;
+        }
+
+        blocking_pool.idle_workers += 1;
+
+        let (guard, wait_result) = BLOCKING_CONDVAR
+            .wait_timeout(blocking_pool, WORKER_IDLE_TIMEOUT)
+            .unwrap();
+
+        blocking_pool = guard;
+        blocking_pool.idle_workers -= 1;
+
+        if wait_result.timed_out() && blocking_pool.jobs.is_empty() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug)]
+pub struct BlockingTask<T> {
+    pub(crate) state_mutex: Arc<Mutex<BlockingTaskState<T>>>,
+    pub(crate) cancelled: Arc<AtomicBool>,
+}
+
+#[derive(Debug)]
+pub struct BlockingTaskState<T> {
+    pub result: Option<Result<T, Box<dyn Any + Send>>>,
+    pub waker: Option<Waker>,
+}
+
+impl<T: fmt::Debug> fmt::Display for BlockingTask<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl<T> Drop for BlockingTask<T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T> Future for BlockingTask<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state_mutex.lock().unwrap();
+
+        match state.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => {
+                state.waker = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::communication::Canceller;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug, Default)]
+pub struct CancelFlag {
+    cancelled: AtomicBool,
+}
+
+impl fmt::Display for CancelFlag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl CancelFlag {
+    #[inline]
+    pub fn set(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    #[inline]
+    pub fn take(&self) -> bool {
+        self.cancelled.swap(false, Ordering::SeqCst)
+    }
+}
+
+impl Canceller for CancelFlag {
+    #[inline]
+    fn cancel(&self) {
+        self.set();
+    }
+}
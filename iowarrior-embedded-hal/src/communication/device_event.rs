@@ -0,0 +1,14 @@
+use crate::communication::IOWarriorDescriptor;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum DeviceEvent {
+    Arrival(IOWarriorDescriptor),
+    Removal(IOWarriorDescriptor),
+}
+
+impl fmt::Display for DeviceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
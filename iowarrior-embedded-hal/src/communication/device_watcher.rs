@@ -0,0 +1,48 @@
+use crate::communication::DeviceEvent;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct DeviceWatcher {
+    pub(crate) event_receiver: Receiver<DeviceEvent>,
+    pub(crate) running: Arc<AtomicBool>,
+    pub(crate) thread: Option<JoinHandle<()>>,
+}
+
+impl fmt::Display for DeviceWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Drop for DeviceWatcher {
+    #[inline]
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl DeviceWatcher {
+    #[inline]
+    pub fn recv(&self) -> Option<DeviceEvent> {
+        self.event_receiver.recv().ok()
+    }
+
+    #[inline]
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<DeviceEvent> {
+        self.event_receiver.recv_timeout(timeout).ok()
+    }
+
+    #[inline]
+    pub fn try_recv(&self) -> Option<DeviceEvent> {
+        self.event_receiver.try_recv().ok()
+    }
+}
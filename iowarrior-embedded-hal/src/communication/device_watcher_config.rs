@@ -0,0 +1,24 @@
+use crate::communication::CommunicationBackend;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct DeviceWatcherConfig {
+    pub backend: CommunicationBackend,
+    pub poll_interval: Duration,
+}
+
+impl Default for DeviceWatcherConfig {
+    fn default() -> Self {
+        DeviceWatcherConfig {
+            backend: CommunicationBackend::Auto,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl fmt::Display for DeviceWatcherConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,225 @@
+use crate::communication::{
+    initialization_service, CommunicationBackend, DeviceEvent, DeviceWatcher, DeviceWatcherConfig,
+    IOWarriorDescriptor, InitializationConfig, InitializationError,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[cfg(all(feature = "ioctrl", target_os = "linux"))]
+type ChangeListener = Option<std::os::fd::OwnedFd>;
+
+#[cfg(not(all(feature = "ioctrl", target_os = "linux")))]
+type ChangeListener = Option<()>;
+
+// IOWKit is not watched: its device list is fixed while a session is open.
+const AUTO_BACKENDS: [CommunicationBackend; 2] =
+    [CommunicationBackend::IOCtrl, CommunicationBackend::USBHID];
+
+pub fn create_device_watcher(
+    config: DeviceWatcherConfig,
+) -> Result<DeviceWatcher, InitializationError> {
+    let descriptors = list_iowarriors(config.backend)?;
+
+    let (event_sender, event_receiver) = mpsc::channel();
+
+    for descriptor in &descriptors {
+        let _ = event_sender.send(DeviceEvent::Arrival(descriptor.clone()));
+    }
+
+    let running = Arc::new(AtomicBool::new(true));
+    let change_listener = create_change_listener();
+
+    let thread = {
+        let running = running.clone();
+
+        thread::Builder::new()
+            .name("iowarrior-device-watcher".to_owned())
+            .spawn(move || {
+                watch_devices(config, running, event_sender, descriptors, change_listener)
+            })
+            .map_err(InitializationError::DeviceWatcherNotStarted)?
+    };
+
+    Ok(DeviceWatcher {
+        event_receiver,
+        running,
+        thread: Some(thread),
+    })
+}
+
+fn watch_devices(
+    config: DeviceWatcherConfig,
+    running: Arc<AtomicBool>,
+    event_sender: Sender<DeviceEvent>,
+    mut known_descriptors: Vec<IOWarriorDescriptor>,
+    change_listener: ChangeListener,
+) {
+    while running.load(Ordering::Relaxed) {
+        wait_for_change(&change_listener, config.poll_interval);
+
+        let descriptors = match list_iowarriors(config.backend) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+
+        let removed = known_descriptors
+            .iter()
+            .filter(|x| !contains_iowarrior(&descriptors, x))
+            .map(|x| DeviceEvent::Removal(x.clone()));
+
+        let arrived = descriptors
+            .iter()
+            .filter(|x| !contains_iowarrior(&known_descriptors, x))
+            .map(|x| DeviceEvent::Arrival(x.clone()));
+
+        for event in removed.chain(arrived) {
+            if event_sender.send(event).is_err() {
+                return;
+            }
+        }
+
+        known_descriptors = descriptors;
+    }
+}
+
+fn list_iowarriors(
+    backend: CommunicationBackend,
+) -> Result<Vec<IOWarriorDescriptor>, InitializationError> {
+    let backends: Vec<CommunicationBackend> = match backend {
+        CommunicationBackend::Auto => AUTO_BACKENDS
+            .into_iter()
+            .filter(|x| x.is_available())
+            .collect(),
+        backend => vec![backend],
+    };
+
+    if backends.is_empty() {
+        return Err(InitializationError::BackendNotAvailable(backend));
+    }
+
+    let mut vec: Vec<IOWarriorDescriptor> = Vec::new();
+
+    for backend in backends {
+        vec.extend(initialization_service::list_iowarriors_with_config(
+            InitializationConfig {
+                backend,
+                ..Default::default()
+            },
+        )?);
+    }
+
+    Ok(vec)
+}
+
+// Legacy IOWarrior40s share an empty serial number, so devices are also told apart by path.
+fn contains_iowarrior(
+    descriptors: &[IOWarriorDescriptor],
+    descriptor: &IOWarriorDescriptor,
+) -> bool {
+    descriptors.iter().any(|x| {
+        x.serial_number == descriptor.serial_number
+            && x.device_type == descriptor.device_type
+            && x.backend == descriptor.backend
+            && x.device_path == descriptor.device_path
+    })
+}
+
+#[cfg(all(feature = "ioctrl", target_os = "linux"))]
+fn create_change_listener() -> ChangeListener {
+    use nix::sys::socket::{
+        bind, socket, AddressFamily, NetlinkAddr, SockFlag, SockProtocol, SockType,
+    };
+    use std::os::fd::AsRawFd;
+
+    let socket = socket(
+        AddressFamily::Netlink,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        SockProtocol::NetlinkKObjectUEvent,
+    )
+    .ok()?;
+
+    bind(socket.as_raw_fd(), &NetlinkAddr::new(0, 1)).ok()?;
+
+    Some(socket)
+}
+
+#[cfg(all(feature = "ioctrl", target_os = "linux"))]
+fn wait_for_change(change_listener: &ChangeListener, timeout: Duration) {
+    use nix::sys::socket::{recv, setsockopt, sockopt, MsgFlags};
+    use nix::sys::time::{TimeVal, TimeValLike};
+    use std::os::fd::AsRawFd;
+    use std::time::Instant;
+
+    let socket = match change_listener {
+        None => return thread::sleep(timeout),
+        Some(x) => x,
+    };
+
+    // Other uevents must not extend the wait, the watcher has to check `running` again.
+    let deadline = Instant::now() + timeout;
+    let mut buffer = [0u8; 4096];
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+
+        if remaining.is_zero() {
+            return;
+        }
+
+        let receive_timeout = TimeVal::microseconds(remaining.as_micros().max(1) as i64);
+
+        if setsockopt(socket, sockopt::ReceiveTimeout, &receive_timeout).is_err() {
+            return thread::sleep(remaining);
+        }
+
+        let bytes_read = match recv(socket.as_raw_fd(), &mut buffer, MsgFlags::empty()) {
+            Err(_) => return,
+            Ok(x) => x,
+        };
+
+        let event = String::from_utf8_lossy(&buffer[..bytes_read]);
+
+        if event.split('\0').any(|x| x == "SUBSYSTEM=usb") {
+            return;
+        }
+    }
+}
+
+#[cfg(not(all(feature = "ioctrl", target_os = "linux")))]
+fn create_change_listener() -> ChangeListener {
+    None
+}
+
+#[cfg(not(all(feature = "ioctrl", target_os = "linux")))]
+fn wait_for_change(change_listener: &ChangeListener, timeout: Duration) {
+    thread::sleep(timeout);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_watcher_reports_present_devices() {
+        let config = DeviceWatcherConfig {
+            backend: CommunicationBackend::Simulation,
+            poll_interval: Duration::from_millis(10),
+        };
+
+        let device_watcher = create_device_watcher(config).unwrap();
+        let descriptors = list_iowarriors(CommunicationBackend::Simulation).unwrap();
+
+        for descriptor in descriptors {
+            assert_eq!(
+                device_watcher.recv(),
+                Some(DeviceEvent::Arrival(descriptor))
+            );
+        }
+
+        assert_eq!(device_watcher.recv_timeout(Duration::from_millis(50)), None);
+    }
+}
@@ -0,0 +1,49 @@
+use crate::communication::{CancelFlag, Canceller, Transport, TransportError, USBInfo};
+use crate::iowarrior::Report;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+pub struct DisconnectedTransport {
+    pub usb_info: USBInfo,
+}
+
+impl fmt::Display for DisconnectedTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Transport for DisconnectedTransport {
+    fn write_report(&mut self, report: &Report) -> Result<(), TransportError> {
+        Err(TransportError::Disconnected)
+    }
+
+    fn read_report_non_blocking(
+        &mut self,
+        report: Report,
+    ) -> Result<Option<Report>, TransportError> {
+        Err(TransportError::Disconnected)
+    }
+
+    fn read_report(&mut self, report: Report) -> Result<Report, TransportError> {
+        Err(TransportError::Disconnected)
+    }
+
+    fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<(), TransportError> {
+        Ok(())
+    }
+
+    fn get_canceller(&self) -> Arc<dyn Canceller> {
+        Arc::new(CancelFlag::default())
+    }
+
+    fn get_usb_info(&self) -> USBInfo {
+        self.usb_info.clone()
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct InitializationConfig {
+    pub backend: CommunicationBackend,
+    pub trace_directory: Option<PathBuf>,
+    pub reconnect: bool,
+    pub read_timeout: Option<Duration>,
+    pub write_timeout: Option<Duration>,
+    pub iowkit_library_path: Option<PathBuf>,
+}
+
+impl Default for InitializationConfig {
+    fn default() -> Self {
+        InitializationConfig {
+            backend: CommunicationBackend::Auto,
+            trace_directory: None,
+            reconnect: false,
+            read_timeout: None,
+            write_timeout: None,
+            iowkit_library_path: None,
+        }
+    }
+}
+
+impl fmt::Display for InitializationConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CommunicationBackend {
+    Auto,
+    IOWKit,
+    IOCtrl,
+    USBHID,
+    LibUSB,
+    Simulation,
+}
+
+impl fmt::Display for CommunicationBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl CommunicationBackend {
+    pub const fn is_available(&self) -> bool {
+        match self {
+            CommunicationBackend::Auto => true,
+            CommunicationBackend::IOWKit => {
+                cfg!(all(
+                    feature = "iowkit",
+                    any(target_os = "windows", target_os = "linux")
+                ))
+            }
+            CommunicationBackend::IOCtrl => cfg!(all(feature = "ioctrl", target_os = "linux")),
+            CommunicationBackend::USBHID => cfg!(all(
+                feature = "usbhid",
+                any(target_os = "windows", target_os = "linux")
+            )),
+            CommunicationBackend::LibUSB => cfg!(feature = "libusb"),
+            CommunicationBackend::Simulation => cfg!(any(feature = "sim", test)),
+        }
+    }
+}
@@ -1,13 +1,86 @@
-use hidapi::HidError;
+use crate::communication::{CommunicationBackend, TransportError};
+use crate::iowarrior::IOWarriorType;
+use std::io;
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum InitializationError {
-    #[error("USB HID error.")]
-    ErrorUSB(HidError),
-    #[error("Internal error: {0}")]
-    InternalError(String),
-    #[error("IOWarrior not found with serial number: {0}")]
+    #[error("USB transport error.")]
+    ErrorUSB(#[source] TransportError),
+    #[error("IOWarrior not found: {0}")]
     NotFound(String),
+    #[error("Empty serial number, IOWarriors without serial number are found by path or type.")]
+    EmptySerialNumber,
+    #[error("Communication backend not available: {0}")]
+    BackendNotAvailable(CommunicationBackend),
+    #[error("Permission denied for IOWarrior device {path}, check the device permissions.")]
+    PermissionDenied {
+        path: String,
+        #[source]
+        error: io::Error,
+    },
+    #[error("Error opening IOWarrior device {path}.")]
+    OpenFailed {
+        path: String,
+        #[source]
+        error: io::Error,
+    },
+    #[error("Error enumerating IOWarrior devices in {path}.")]
+    EnumerationFailed {
+        path: String,
+        #[source]
+        error: io::Error,
+    },
+    #[error("Device path is not valid UTF-8: {path:?}")]
+    InvalidPath { path: PathBuf },
+    #[error("Ioctl failed on IOWarrior device {path}, errno {errno}.")]
+    IoctlFailed { path: String, errno: i32 },
+    #[error("Interface {interface} is missing on IOWarrior device {path}.")]
+    MissingInterface { path: String, interface: u8 },
+    #[error("Interrupt endpoint is missing on interface {interface} of IOWarrior device {path}.")]
+    MissingEndpoint { path: String, interface: u8 },
+    #[error("Error reading serial number of IOWarrior device {path}.")]
+    InvalidSerialNumber { path: String },
+    #[error("Error reading revision of IOWarrior device {path}.")]
+    InvalidRevision { path: String },
+    #[error("Invalid trace file {path}, line {line}.")]
+    InvalidTrace { path: PathBuf, line: usize },
+    #[error("Reconnect is not supported for IOWarrior {device_type} without serial number.")]
+    ReconnectNotSupported { device_type: IOWarriorType },
+    #[error("Error starting device watcher.")]
+    DeviceWatcherNotStarted(#[source] io::Error),
+    #[cfg(feature = "iowkit")]
+    #[error("iowkit library not found, tried: {candidates:?}")]
+    IowkitLibraryNotFound { candidates: Vec<PathBuf> },
+    #[cfg(feature = "iowkit")]
+    #[error("Error loading iowkit library: {path}")]
+    IowkitLibraryNotLoaded {
+        path: PathBuf,
+        #[source]
+        error: libloading::Error,
+    },
+    #[cfg(feature = "iowkit")]
+    #[error("Unsupported iowkit library version: {version}, minimum version: {minimum_version}")]
+    IowkitVersionNotSupported {
+        version: String,
+        minimum_version: String,
+    },
+}
+
+impl InitializationError {
+    pub(crate) fn from_open_error(path: &str, error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::PermissionDenied => InitializationError::PermissionDenied {
+                path: path.to_owned(),
+                error,
+            },
+            io::ErrorKind::NotFound => InitializationError::NotFound(path.to_owned()),
+            _ => InitializationError::OpenFailed {
+                path: path.to_owned(),
+                error,
+            },
+        }
+    }
 }
@@ -0,0 +1,442 @@
+use crate::communication::{
+    device_watcher_service, CommunicationBackend, DevicePath, DeviceWatcher, DeviceWatcherConfig,
+    IOWarriorDescriptor, InitializationConfig, InitializationError, Transport,
+};
+use crate::iowarrior::{iowarrior_service, IOWarrior, IOWarriorType};
+
+pub use crate::communication::replay::initialization_service::get_replayed_iowarrior;
+#[cfg(any(feature = "sim", test))]
+pub use crate::communication::sim::initialization_service::{
+    get_simulated_iowarrior, get_simulated_iowarrior_with_config,
+};
+
+const AUTO_BACKENDS: [CommunicationBackend; 4] = [
+    CommunicationBackend::IOWKit,
+    CommunicationBackend::IOCtrl,
+    CommunicationBackend::USBHID,
+    CommunicationBackend::LibUSB,
+];
+
+pub fn list_iowarriors() -> Result<Vec<IOWarriorDescriptor>, InitializationError> {
+    list_iowarriors_with_config(InitializationConfig::default())
+}
+
+pub fn list_iowarriors_with_config(
+    config: InitializationConfig,
+) -> Result<Vec<IOWarriorDescriptor>, InitializationError> {
+    match config.backend {
+        CommunicationBackend::Auto => {
+            let mut result = Err(InitializationError::BackendNotAvailable(
+                CommunicationBackend::Auto,
+            ));
+
+            for backend in AUTO_BACKENDS.into_iter().filter(|x| x.is_available()) {
+                match list_iowarriors_from_backend(backend, &config) {
+                    Ok(descriptors) if !descriptors.is_empty() => return Ok(descriptors),
+                    Ok(descriptors) => result = Ok(descriptors),
+                    Err(error) => {
+                        if result.is_err() {
+                            result = Err(error);
+                        }
+                    }
+                }
+            }
+
+            result
+        }
+        backend => list_iowarriors_from_backend(backend, &config),
+    }
+}
+
+pub fn get_iowarriors() -> Result<Vec<IOWarrior>, InitializationError> {
+    get_iowarriors_with_config(InitializationConfig::default())
+}
+
+pub fn get_iowarriors_with_config(
+    config: InitializationConfig,
+) -> Result<Vec<IOWarrior>, InitializationError> {
+    match config.backend {
+        CommunicationBackend::Auto => {
+            let mut result = Err(InitializationError::BackendNotAvailable(
+                CommunicationBackend::Auto,
+            ));
+
+            for backend in AUTO_BACKENDS.into_iter().filter(|x| x.is_available()) {
+                match get_iowarriors_from_backend(backend, &config) {
+                    Ok(iowarriors) if !iowarriors.is_empty() => return Ok(iowarriors),
+                    Ok(iowarriors) => {
+                        if matches!(result, Err(InitializationError::BackendNotAvailable(_))) {
+                            result = Ok(iowarriors);
+                        }
+                    }
+                    Err(error) => {
+                        result = match result {
+                            Ok(_) => Err(error),
+                            Err(result) => Err(merge_errors(result, error)),
+                        }
+                    }
+                }
+            }
+
+            result
+        }
+        backend => get_iowarriors_from_backend(backend, &config),
+    }
+}
+
+pub fn get_iowarrior(serial_number: &str) -> Result<IOWarrior, InitializationError> {
+    get_iowarrior_with_config(serial_number, InitializationConfig::default())
+}
+
+pub fn get_iowarrior_with_config(
+    serial_number: &str,
+    config: InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    // Devices without serial number, like legacy IOWarrior40s, are only found by path or type.
+    if serial_number.is_empty() {
+        return Err(InitializationError::EmptySerialNumber);
+    }
+
+    match config.backend {
+        CommunicationBackend::Auto => {
+            let mut result = InitializationError::BackendNotAvailable(CommunicationBackend::Auto);
+
+            for backend in AUTO_BACKENDS.into_iter().filter(|x| x.is_available()) {
+                match get_iowarrior_from_backend(serial_number, backend, &config) {
+                    Ok(iowarrior) => return Ok(iowarrior),
+                    Err(error) => result = merge_errors(result, error),
+                }
+            }
+
+            Err(result)
+        }
+        backend => get_iowarrior_from_backend(serial_number, backend, &config),
+    }
+}
+
+pub fn get_iowarrior_by_path(device_path: &DevicePath) -> Result<IOWarrior, InitializationError> {
+    get_iowarrior_by_path_with_config(device_path, InitializationConfig::default())
+}
+
+pub fn get_iowarrior_by_path_with_config(
+    device_path: &DevicePath,
+    config: InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    match config.backend {
+        CommunicationBackend::Auto => {
+            let mut result = InitializationError::BackendNotAvailable(CommunicationBackend::Auto);
+
+            for backend in AUTO_BACKENDS.into_iter().filter(|x| x.is_available()) {
+                match get_iowarrior_by_path_from_backend(device_path, backend, &config) {
+                    Ok(iowarrior) => return Ok(iowarrior),
+                    Err(error) => result = merge_errors(result, error),
+                }
+            }
+
+            Err(result)
+        }
+        backend => get_iowarrior_by_path_from_backend(device_path, backend, &config),
+    }
+}
+
+pub fn get_iowarrior_by_type(device_type: IOWarriorType) -> Result<IOWarrior, InitializationError> {
+    get_iowarrior_by_type_with_config(device_type, InitializationConfig::default())
+}
+
+pub fn get_iowarrior_by_type_with_config(
+    device_type: IOWarriorType,
+    config: InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let product_type = get_product_type(device_type);
+
+    let mut result = InitializationError::NotFound(device_type.to_string());
+
+    for descriptor in list_iowarriors_with_config(config.clone())?
+        .into_iter()
+        .filter(|x| get_product_type(x.device_type) == product_type)
+    {
+        // A device that fails to open, e.g. one in use, must not hide the next candidate.
+        match get_iowarrior_by_path_from_backend(
+            &descriptor.device_path,
+            descriptor.backend,
+            &config,
+        ) {
+            Ok(iowarrior) if iowarrior.get_type() == device_type => return Ok(iowarrior),
+            Ok(_) => {}
+            Err(error) => result = merge_errors(result, error),
+        }
+    }
+
+    Err(result)
+}
+
+pub fn get_iowarrior_with_transport(
+    device_type: IOWarriorType,
+    device_revision: u16,
+    device_serial: &str,
+    transport: Box<dyn Transport>,
+) -> Result<IOWarrior, InitializationError> {
+    iowarrior_service::create_iowarrior(
+        device_type,
+        device_revision,
+        String::from(device_serial),
+        transport,
+        &InitializationConfig::default(),
+    )
+}
+
+pub fn get_device_watcher() -> Result<DeviceWatcher, InitializationError> {
+    get_device_watcher_with_config(DeviceWatcherConfig::default())
+}
+
+pub fn get_device_watcher_with_config(
+    config: DeviceWatcherConfig,
+) -> Result<DeviceWatcher, InitializationError> {
+    device_watcher_service::create_device_watcher(config)
+}
+
+fn list_iowarriors_from_backend(
+    backend: CommunicationBackend,
+    config: &InitializationConfig,
+) -> Result<Vec<IOWarriorDescriptor>, InitializationError> {
+    match backend {
+        #[cfg(all(feature = "iowkit", any(target_os = "windows", target_os = "linux")))]
+        CommunicationBackend::IOWKit => {
+            crate::communication::iowkit::initialization_service::list_iowarriors(config)
+        }
+        #[cfg(all(feature = "ioctrl", target_os = "linux"))]
+        CommunicationBackend::IOCtrl => {
+            crate::communication::ioctrl::initialization_service::list_iowarriors()
+        }
+        #[cfg(all(feature = "usbhid", any(target_os = "windows", target_os = "linux")))]
+        CommunicationBackend::USBHID => {
+            crate::communication::usbhid::initialization_service::list_iowarriors()
+        }
+        #[cfg(feature = "libusb")]
+        CommunicationBackend::LibUSB => {
+            crate::communication::libusb::initialization_service::list_iowarriors()
+        }
+        #[cfg(any(feature = "sim", test))]
+        CommunicationBackend::Simulation => {
+            crate::communication::sim::initialization_service::list_iowarriors()
+        }
+        _ => Err(InitializationError::BackendNotAvailable(backend)),
+    }
+}
+
+fn get_iowarrior_by_path_from_backend(
+    device_path: &DevicePath,
+    backend: CommunicationBackend,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    match backend {
+        #[cfg(all(feature = "iowkit", any(target_os = "windows", target_os = "linux")))]
+        CommunicationBackend::IOWKit => {
+            crate::communication::iowkit::initialization_service::get_iowarrior_by_path(
+                device_path,
+                config,
+            )
+        }
+        #[cfg(all(feature = "ioctrl", target_os = "linux"))]
+        CommunicationBackend::IOCtrl => {
+            crate::communication::ioctrl::initialization_service::get_iowarrior_by_path(
+                device_path,
+                config,
+            )
+        }
+        #[cfg(all(feature = "usbhid", any(target_os = "windows", target_os = "linux")))]
+        CommunicationBackend::USBHID => {
+            crate::communication::usbhid::initialization_service::get_iowarrior_by_path(
+                device_path,
+                config,
+            )
+        }
+        #[cfg(feature = "libusb")]
+        CommunicationBackend::LibUSB => {
+            crate::communication::libusb::initialization_service::get_iowarrior_by_path(
+                device_path,
+                config,
+            )
+        }
+        #[cfg(any(feature = "sim", test))]
+        CommunicationBackend::Simulation => {
+            crate::communication::sim::initialization_service::get_iowarrior_by_path(
+                device_path,
+                config,
+            )
+        }
+        _ => Err(InitializationError::BackendNotAvailable(backend)),
+    }
+}
+
+fn get_product_type(device_type: IOWarriorType) -> IOWarriorType {
+    match device_type {
+        IOWarriorType::IOWarrior56Dongle => IOWarriorType::IOWarrior56,
+        IOWarriorType::IOWarrior28Dongle => IOWarriorType::IOWarrior28,
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior28L
+        | IOWarriorType::IOWarrior56
+        | IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior100 => device_type,
+    }
+}
+
+// A concrete error, e.g. a device that could not be opened, takes priority over NotFound.
+fn merge_errors(result: InitializationError, error: InitializationError) -> InitializationError {
+    match result {
+        InitializationError::NotFound(_) | InitializationError::BackendNotAvailable(_) => error,
+        result => result,
+    }
+}
+
+fn get_iowarriors_from_backend(
+    backend: CommunicationBackend,
+    config: &InitializationConfig,
+) -> Result<Vec<IOWarrior>, InitializationError> {
+    match backend {
+        #[cfg(all(feature = "iowkit", any(target_os = "windows", target_os = "linux")))]
+        CommunicationBackend::IOWKit => {
+            crate::communication::iowkit::initialization_service::get_iowarriors(config)
+        }
+        #[cfg(all(feature = "ioctrl", target_os = "linux"))]
+        CommunicationBackend::IOCtrl => {
+            crate::communication::ioctrl::initialization_service::get_iowarriors(config)
+        }
+        #[cfg(all(feature = "usbhid", any(target_os = "windows", target_os = "linux")))]
+        CommunicationBackend::USBHID => {
+            crate::communication::usbhid::initialization_service::get_iowarriors(config)
+        }
+        #[cfg(feature = "libusb")]
+        CommunicationBackend::LibUSB => {
+            crate::communication::libusb::initialization_service::get_iowarriors(config)
+        }
+        #[cfg(any(feature = "sim", test))]
+        CommunicationBackend::Simulation => {
+            crate::communication::sim::initialization_service::get_iowarriors(config)
+        }
+        _ => Err(InitializationError::BackendNotAvailable(backend)),
+    }
+}
+
+fn get_iowarrior_from_backend(
+    serial_number: &str,
+    backend: CommunicationBackend,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    match backend {
+        #[cfg(all(feature = "iowkit", any(target_os = "windows", target_os = "linux")))]
+        CommunicationBackend::IOWKit => {
+            crate::communication::iowkit::initialization_service::get_iowarrior(
+                serial_number,
+                config,
+            )
+        }
+        #[cfg(all(feature = "ioctrl", target_os = "linux"))]
+        CommunicationBackend::IOCtrl => {
+            crate::communication::ioctrl::initialization_service::get_iowarrior(
+                serial_number,
+                config,
+            )
+        }
+        #[cfg(all(feature = "usbhid", any(target_os = "windows", target_os = "linux")))]
+        CommunicationBackend::USBHID => {
+            crate::communication::usbhid::initialization_service::get_iowarrior(
+                serial_number,
+                config,
+            )
+        }
+        #[cfg(feature = "libusb")]
+        CommunicationBackend::LibUSB => {
+            crate::communication::libusb::initialization_service::get_iowarrior(
+                serial_number,
+                config,
+            )
+        }
+        #[cfg(any(feature = "sim", test))]
+        CommunicationBackend::Simulation => {
+            crate::communication::sim::initialization_service::get_iowarrior(serial_number, config)
+        }
+        _ => Err(InitializationError::BackendNotAvailable(backend)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_simulation_config() -> InitializationConfig {
+        InitializationConfig {
+            backend: CommunicationBackend::Simulation,
+            ..InitializationConfig::default()
+        }
+    }
+
+    #[test]
+    fn listed_devices_open_by_path() {
+        for descriptor in list_iowarriors_with_config(get_simulation_config()).unwrap() {
+            let iowarrior =
+                get_iowarrior_by_path_with_config(&descriptor.device_path, get_simulation_config())
+                    .unwrap();
+
+            assert_eq!(iowarrior.get_type(), descriptor.device_type);
+            assert_eq!(iowarrior.get_serial_number(), descriptor.serial_number);
+        }
+
+        assert!(matches!(
+            get_iowarrior_by_path_with_config(
+                &DevicePath::Path(String::from("/dev/null")),
+                get_simulation_config()
+            ),
+            Err(InitializationError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn empty_serial_number_is_rejected() {
+        assert!(matches!(
+            get_iowarrior_with_config("", get_simulation_config()),
+            Err(InitializationError::EmptySerialNumber)
+        ));
+    }
+
+    #[test]
+    fn concrete_errors_take_priority_over_not_found() {
+        let not_found = || InitializationError::NotFound(String::from("00000001"));
+        let open_failed = || InitializationError::OpenFailed {
+            path: String::from("/dev/usb/iowarrior0"),
+            error: std::io::Error::from(std::io::ErrorKind::ResourceBusy),
+        };
+
+        assert!(matches!(
+            merge_errors(not_found(), open_failed()),
+            InitializationError::OpenFailed { .. }
+        ));
+        assert!(matches!(
+            merge_errors(open_failed(), not_found()),
+            InitializationError::OpenFailed { .. }
+        ));
+        assert!(matches!(
+            merge_errors(
+                InitializationError::BackendNotAvailable(CommunicationBackend::Auto),
+                not_found()
+            ),
+            InitializationError::NotFound(_)
+        ));
+    }
+
+    #[test]
+    fn device_type_lookup_skips_other_variants() {
+        for device_type in [
+            IOWarriorType::IOWarrior56,
+            IOWarriorType::IOWarrior56Dongle,
+            IOWarriorType::IOWarrior28L,
+        ] {
+            let iowarrior =
+                get_iowarrior_by_type_with_config(device_type, get_simulation_config()).unwrap();
+
+            assert_eq!(iowarrior.get_type(), device_type);
+        }
+    }
+}
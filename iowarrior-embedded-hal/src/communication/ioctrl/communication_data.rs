@@ -1,5 +1,8 @@
+use crate::communication::{CancelFlag, USBInfo};
 use std::fmt;
 use std::fs::File;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct USBPipe {
@@ -36,6 +39,10 @@ impl fmt::Display for USBPipes {
 #[derive(Debug)]
 pub struct CommunicationData {
     pub usb_pipes: USBPipes,
+    pub read_timeout: Option<Duration>,
+    pub write_timeout: Option<Duration>,
+    pub cancel_data: Arc<CancelData>,
+    pub usb_info: USBInfo,
 }
 
 impl fmt::Display for CommunicationData {
@@ -43,3 +50,16 @@ impl fmt::Display for CommunicationData {
         write!(f, "{:?}", self)
     }
 }
+
+#[derive(Debug)]
+pub struct CancelData {
+    pub wakeup_reader: File,
+    pub wakeup_writer: File,
+    pub cancel_flag: CancelFlag,
+}
+
+impl fmt::Display for CancelData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
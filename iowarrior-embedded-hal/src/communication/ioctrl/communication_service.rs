@@ -1,66 +1,171 @@
-use crate::communication::{CommunicationData, USBPipe, USBPipes};
+use crate::communication::ioctrl::{CancelData, CommunicationData, USBPipe, USBPipes};
+use crate::communication::{Canceller, Transport, TransportError, USBInfo};
 use crate::iowarrior::{Pipe, Report};
-use hidapi::HidError;
+use nix::poll::{PollFd, PollFlags, PollTimeout};
+use std::io;
 use std::io::{Read, Write};
+use std::os::fd::AsFd;
+use std::sync::Arc;
+use std::time::Duration;
 
-pub fn write_report(
-    communication_data: &mut CommunicationData,
-    report: &Report,
-) -> Result<(), HidError> {
-    let usb_device = pipe_to_usb_device(&mut communication_data.usb_pipes, report.pipe);
-
-    let bytes_written = usb_device
-        .file
-        .write(&report.buffer[0..])
-        .map_err(|x| HidError::IoError { error: x })?;
-
-    if bytes_written != report.buffer.len() {
-        return Err(HidError::IncompleteSendError {
-            sent: bytes_written,
-            all: report.buffer.len(),
-        });
+impl Transport for CommunicationData {
+    fn write_report(&mut self, report: &Report) -> Result<(), TransportError> {
+        let usb_device = pipe_to_usb_device(&mut self.usb_pipes, report.pipe);
+
+        wait_for_file(usb_device, PollFlags::POLLOUT, self.write_timeout)?;
+
+        let bytes_written = usb_device
+            .file
+            .write(&report.buffer[0..])
+            .map_err(TransportError::from)?;
+
+        if bytes_written != report.buffer.len() {
+            return Err(TransportError::ShortWrite {
+                written: bytes_written,
+                expected: report.buffer.len(),
+            });
+        }
+
+        Ok(())
     }
 
-    Ok(())
-}
+    fn read_report_non_blocking(
+        &mut self,
+        mut report: Report,
+    ) -> Result<Option<Report>, TransportError> {
+        let usb_device = pipe_to_usb_device(&mut self.usb_pipes, report.pipe);
+
+        if !is_file_ready(usb_device, PollFlags::POLLIN)? {
+            return Ok(None);
+        }
+
+        let bytes_read = usb_device.file.read(report.buffer.as_mut_slice())?;
+
+        if bytes_read > 0 && bytes_read != report.buffer.len() {
+            return Err(TransportError::ShortRead {
+                read: bytes_read,
+                expected: report.buffer.len(),
+            });
+        }
+
+        Ok(match bytes_read > 0 {
+            true => Some(report),
+            false => None,
+        })
+    }
+
+    fn read_report(&mut self, mut report: Report) -> Result<Report, TransportError> {
+        let usb_device = pipe_to_usb_device(&mut self.usb_pipes, report.pipe);
+
+        wait_for_read(usb_device, &self.cancel_data, self.read_timeout)?;
+
+        let bytes_read = usb_device.file.read(report.buffer.as_mut_slice())?;
+
+        if bytes_read != report.buffer.len() {
+            return Err(TransportError::ShortRead {
+                read: bytes_read,
+                expected: report.buffer.len(),
+            });
+        }
+
+        Ok(report)
+    }
+
+    fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<(), TransportError> {
+        self.read_timeout = read_timeout;
+        self.write_timeout = write_timeout;
 
-pub fn read_report_non_blocking(
-    communication_data: &mut CommunicationData,
-    mut report: Report,
-) -> Result<Option<Report>, HidError> {
-    let usb_device = pipe_to_usb_device(&mut communication_data.usb_pipes, report.pipe);
+        Ok(())
+    }
 
-    let bytes_read = usb_device.file.read(report.buffer.as_mut_slice())?;
+    fn get_canceller(&self) -> Arc<dyn Canceller> {
+        self.cancel_data.clone()
+    }
 
-    if bytes_read > 0 && bytes_read != report.buffer.len() {
-        return Err(HidError::IncompleteSendError {
-            sent: bytes_read,
-            all: report.buffer.len(),
-        });
+    fn get_usb_info(&self) -> USBInfo {
+        self.usb_info.clone()
     }
+}
 
-    Ok(match bytes_read > 0 {
-        true => Some(report),
-        false => None,
-    })
+impl Canceller for CancelData {
+    fn cancel(&self) {
+        self.cancel_flag.set();
+
+        _ = (&self.wakeup_writer).write(&[0u8]);
+    }
 }
 
-pub fn read_report(
-    communication_data: &mut CommunicationData,
-    mut report: Report,
-) -> Result<Report, HidError> {
-    let usb_device = pipe_to_usb_device(&mut communication_data.usb_pipes, report.pipe);
+fn wait_for_file(
+    usb_device: &USBPipe,
+    poll_flags: PollFlags,
+    timeout: Option<Duration>,
+) -> Result<(), TransportError> {
+    let timeout = match timeout {
+        None => return Ok(()),
+        Some(timeout) => PollTimeout::try_from(timeout).unwrap_or(PollTimeout::MAX),
+    };
 
-    let bytes_read = usb_device.file.read(report.buffer.as_mut_slice())?;
+    let mut poll_fds = [PollFd::new(usb_device.file.as_fd(), poll_flags)];
 
-    if bytes_read != report.buffer.len() {
-        return Err(HidError::IncompleteSendError {
-            sent: bytes_read,
-            all: report.buffer.len(),
-        });
+    match nix::poll::poll(&mut poll_fds, timeout) {
+        Ok(0) => Err(TransportError::Timeout),
+        Ok(_) => Ok(()),
+        Err(error) => Err(TransportError::from(io::Error::from(error))),
     }
+}
+
+fn is_file_ready(usb_device: &USBPipe, poll_flags: PollFlags) -> Result<bool, TransportError> {
+    let mut poll_fds = [PollFd::new(usb_device.file.as_fd(), poll_flags)];
 
-    Ok(report)
+    match nix::poll::poll(&mut poll_fds, PollTimeout::ZERO) {
+        Ok(0) => Ok(false),
+        Ok(_) => Ok(true),
+        Err(error) => Err(TransportError::from(io::Error::from(error))),
+    }
+}
+
+fn wait_for_read(
+    usb_device: &USBPipe,
+    cancel_data: &CancelData,
+    timeout: Option<Duration>,
+) -> Result<(), TransportError> {
+    let timeout = match timeout {
+        None => PollTimeout::NONE,
+        Some(timeout) => PollTimeout::try_from(timeout).unwrap_or(PollTimeout::MAX),
+    };
+
+    loop {
+        if cancel_data.cancel_flag.take() {
+            return Err(TransportError::Cancelled);
+        }
+
+        let mut poll_fds = [
+            PollFd::new(usb_device.file.as_fd(), PollFlags::POLLIN),
+            PollFd::new(cancel_data.wakeup_reader.as_fd(), PollFlags::POLLIN),
+        ];
+
+        match nix::poll::poll(&mut poll_fds, timeout) {
+            Ok(0) => return Err(TransportError::Timeout),
+            Ok(_) => {}
+            Err(error) => return Err(TransportError::from(io::Error::from(error))),
+        }
+
+        let woken_up = poll_fds[1]
+            .revents()
+            .is_some_and(|x| x.contains(PollFlags::POLLIN));
+
+        if !woken_up {
+            return Ok(());
+        }
+
+        (&cancel_data.wakeup_reader)
+            .read(&mut [0u8])
+            .map_err(TransportError::from)?;
+    }
 }
 
 fn pipe_to_usb_device(usb_pipes: &mut USBPipes, pipe: Pipe) -> &mut USBPipe {
@@ -1,15 +1,25 @@
-use crate::communication::{CommunicationData, InitializationError, USBPipe, USBPipes};
+use crate::communication::ioctrl::{CancelData, CommunicationData, USBPipe, USBPipes};
+use crate::communication::{
+    CancelFlag, CommunicationBackend, DevicePath, IOWarriorDescriptor, InitializationConfig,
+    InitializationError, TransportError, USBInfo, USBSpeed,
+};
 use crate::iowarrior::{iowarrior_service, IOWarrior, IOWarriorType};
-use hidapi::HidError::IoError;
 use itertools::Itertools;
+use std::collections::HashMap;
 use std::ffi::CStr;
-use std::fmt;
-use std::fs::OpenOptions;
+use std::fs::{File, OpenOptions};
 use std::os::fd::AsRawFd;
 use std::os::raw;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, io};
 
 const VENDOR_IDENTIFIER: i32 = 1984;
 
+const USBMISC_CLASS_PATH: &str = "/sys/class/usbmisc";
+
+const DEVICE_PATH_PATTERN: &str = "/dev/usb/iowarrior*";
+
 #[repr(C)]
 #[derive(Debug)]
 struct IoctlInfo {
@@ -33,10 +43,14 @@ impl fmt::Display for IoctlInfo {
 
 #[derive(Debug)]
 struct IOWarriorInfo {
+    device_path: String,
     usb_pipe: USBPipe,
     device_type: IOWarriorType,
     device_revision: u16,
     device_serial: String,
+    usb_speed: Option<USBSpeed>,
+    max_power: Option<u16>,
+    packet_size: u16,
 }
 
 impl fmt::Display for IOWarriorInfo {
@@ -45,17 +59,57 @@ impl fmt::Display for IOWarriorInfo {
     }
 }
 
-pub fn get_iowarriors() -> Result<Vec<IOWarrior>, InitializationError> {
-    let device_list = get_device_list()?;
+#[derive(Debug)]
+struct InterfaceInfo {
+    usb_device_path: PathBuf,
+    interface: u8,
+    device_path: String,
+}
 
-    let grouped_usb_devices = device_list
+impl fmt::Display for InterfaceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+pub fn list_iowarriors() -> Result<Vec<IOWarriorDescriptor>, InitializationError> {
+    let grouped_interfaces = get_interface_list()?
         .into_iter()
-        .into_group_map_by(|iowarrior_info| iowarrior_info.device_serial.clone());
+        .into_group_map_by(|interface_info| interface_info.usb_device_path.clone());
+
+    let mut vec: Vec<IOWarriorDescriptor> = Vec::new();
+
+    for (usb_device_path, interface_infos) in grouped_interfaces {
+        if let Some(descriptor) = get_iowarrior_descriptor(&usb_device_path, &interface_infos) {
+            vec.push(descriptor);
+        }
+    }
+
+    vec.sort();
+
+    Ok(vec)
+}
+
+pub fn get_iowarriors(
+    config: &InitializationConfig,
+) -> Result<Vec<IOWarrior>, InitializationError> {
+    let interface_list = get_interface_list()?;
+    let device_list = get_device_list()?;
+
+    // Legacy IOWarrior40s have no serial number, so interfaces are grouped by USB device first.
+    let grouped_usb_devices = device_list.into_iter().into_group_map_by(|iowarrior_info| {
+        let usb_device_path = interface_list
+            .iter()
+            .find(|x| x.device_path == iowarrior_info.device_path)
+            .map(|x| x.usb_device_path.clone());
+
+        (usb_device_path, iowarrior_info.device_serial.clone())
+    });
 
     let mut vec: Vec<IOWarrior> = Vec::new();
 
-    for (serial_number, device_infos) in grouped_usb_devices {
-        let iowarrior = get_iowarrior_internal(device_infos, &serial_number)?;
+    for ((_, serial_number), device_infos) in grouped_usb_devices {
+        let iowarrior = get_iowarrior_internal(device_infos, &serial_number, config)?;
 
         vec.push(iowarrior);
     }
@@ -63,7 +117,10 @@ pub fn get_iowarriors() -> Result<Vec<IOWarrior>, InitializationError> {
     Ok(vec)
 }
 
-pub fn get_iowarrior(serial_number: &str) -> Result<IOWarrior, InitializationError> {
+pub fn get_iowarrior(
+    serial_number: &str,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
     let device_list: Vec<IOWarriorInfo> = get_device_list()?;
 
     let grouped_usb_device: Vec<_> = device_list
@@ -75,12 +132,49 @@ pub fn get_iowarrior(serial_number: &str) -> Result<IOWarrior, InitializationErr
         return Err(InitializationError::NotFound(String::from(serial_number)));
     }
 
-    get_iowarrior_internal(grouped_usb_device, serial_number)
+    get_iowarrior_internal(grouped_usb_device, serial_number, config)
+}
+
+pub fn get_iowarrior_by_path(
+    device_path: &DevicePath,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let interface_list = get_interface_list()?;
+
+    let usb_device_path = match interface_list.iter().find(|x| match device_path {
+        DevicePath::Path(path) => x.device_path == *path,
+        DevicePath::Location(location) => {
+            x.usb_device_path.file_name().and_then(|y| y.to_str()) == Some(location.as_str())
+        }
+        DevicePath::Index(_) => false,
+    }) {
+        None => return Err(InitializationError::NotFound(device_path.to_string())),
+        Some(x) => x.usb_device_path.clone(),
+    };
+
+    let mut device_infos: Vec<IOWarriorInfo> = Vec::new();
+
+    for interface_info in interface_list
+        .iter()
+        .filter(|x| x.usb_device_path == usb_device_path)
+    {
+        if let Some(iowarrior_info) = open_device_info(&interface_info.device_path)? {
+            device_infos.push(iowarrior_info);
+        }
+    }
+
+    let serial_number = match device_infos.first() {
+        None => return Err(InitializationError::NotFound(device_path.to_string())),
+        Some(x) => x.device_serial.clone(),
+    };
+
+    get_iowarrior_internal(device_infos, &serial_number, config)
 }
 
 fn get_iowarrior_internal(
     device_infos: Vec<IOWarriorInfo>,
     serial_number: &str,
+    config: &InitializationConfig,
 ) -> Result<IOWarrior, InitializationError> {
     let iowarrior_info = device_infos.iter().next().unwrap();
 
@@ -88,137 +182,102 @@ fn get_iowarrior_internal(
     let device_revision = iowarrior_info.device_revision.clone();
     let device_serial = iowarrior_info.device_serial.clone();
 
+    let usb_info = USBInfo {
+        speed: iowarrior_info.usb_speed,
+        max_power: iowarrior_info.max_power,
+        packet_sizes: device_infos
+            .iter()
+            .sorted_by_key(|x| x.usb_pipe.interface)
+            .map(|x| x.packet_size)
+            .collect(),
+    };
+
     let usb_pipes = get_usb_pipes(device_type, device_infos)?;
 
-    let communication_data = CommunicationData { usb_pipes };
+    let (wakeup_reader, wakeup_writer) = nix::unistd::pipe()
+        .map_err(|x| InitializationError::ErrorUSB(TransportError::IO(x.into())))?;
+
+    let communication_data = CommunicationData {
+        usb_pipes,
+        read_timeout: None,
+        write_timeout: None,
+        cancel_data: Arc::new(CancelData {
+            wakeup_reader: File::from(wakeup_reader),
+            wakeup_writer: File::from(wakeup_writer),
+            cancel_flag: CancelFlag::default(),
+        }),
+        usb_info,
+    };
 
     iowarrior_service::create_iowarrior(
         device_type,
         device_revision,
         device_serial,
-        communication_data,
+        Box::new(communication_data),
+        config,
     )
-    .map_err(|x| InitializationError::ErrorUSB(x))
 }
 
 fn get_usb_pipes(
     device_type: IOWarriorType,
-    mut device_infos: Vec<IOWarriorInfo>,
+    device_infos: Vec<IOWarriorInfo>,
 ) -> Result<USBPipes, InitializationError> {
-    device_infos.sort_by(|a, b| {
-        a.usb_pipe
-            .interface
-            .partial_cmp(&b.usb_pipe.interface)
-            .unwrap()
-    });
+    let device_path = device_infos
+        .iter()
+        .min_by_key(|x| x.usb_pipe.interface)
+        .map(|x| x.device_path.clone())
+        .unwrap_or_default();
+
+    let mut usb_pipes: HashMap<u8, USBPipe> = device_infos
+        .into_iter()
+        .map(|x| (x.usb_pipe.interface, x.usb_pipe))
+        .collect();
 
-    let mut iterator = device_infos.into_iter();
+    let mut take_pipe = |interface: u8| {
+        usb_pipes
+            .remove(&interface)
+            .ok_or_else(|| InitializationError::MissingInterface {
+                path: device_path.clone(),
+                interface,
+            })
+    };
 
     Ok(match device_type {
         IOWarriorType::IOWarrior28
         | IOWarriorType::IOWarrior28Dongle
-        | IOWarriorType::IOWarrior100 => {
-            let usb_0 = iterator.next().unwrap();
-            let usb_1 = iterator.next().unwrap();
-            let usb_2 = iterator.next().unwrap();
-            let usb_3 = iterator.next().unwrap();
-
-            USBPipes::Extended {
-                pipe_0: usb_0.usb_pipe,
-                pipe_1: usb_1.usb_pipe,
-                pipe_2: usb_2.usb_pipe,
-                pipe_3: usb_3.usb_pipe,
-            }
-        }
+        | IOWarriorType::IOWarrior100 => USBPipes::Extended {
+            pipe_0: take_pipe(0)?,
+            pipe_1: take_pipe(1)?,
+            pipe_2: take_pipe(2)?,
+            pipe_3: take_pipe(3)?,
+        },
         IOWarriorType::IOWarrior40
         | IOWarriorType::IOWarrior24
         | IOWarriorType::IOWarrior24PowerVampire
         | IOWarriorType::IOWarrior28L
         | IOWarriorType::IOWarrior56
-        | IOWarriorType::IOWarrior56Dongle => {
-            let usb_0 = iterator.next().unwrap();
-            let usb_1 = iterator.next().unwrap();
-
-            USBPipes::Standard {
-                pipe_0: usb_0.usb_pipe,
-                pipe_1: usb_1.usb_pipe,
-            }
-        }
+        | IOWarriorType::IOWarrior56Dongle => USBPipes::Standard {
+            pipe_0: take_pipe(0)?,
+            pipe_1: take_pipe(1)?,
+        },
     })
 }
 
 fn get_device_list() -> Result<Vec<IOWarriorInfo>, InitializationError> {
     let mut device_list: Vec<IOWarriorInfo> = Vec::new();
 
-    for glob_result in glob::glob("/dev/usb/iowarrior*")
-        .map_err(|x| InitializationError::InternalError("Error getting device list.".to_owned()))?
-    {
-        let entry = glob_result.map_err(|x| {
-            InitializationError::InternalError("Error getting device list.".to_owned())
-        })?;
+    for glob_result in glob_paths(DEVICE_PATH_PATTERN)? {
+        let entry = glob_result.map_err(to_enumeration_error)?;
 
         match entry.to_str() {
-            None => {
-                return Err(InitializationError::InternalError(
-                    "Error getting device list.".to_owned(),
-                ))
-            }
+            None => return Err(InitializationError::InvalidPath { path: entry }),
             Some(device_path) => {
-                let file = OpenOptions::new()
-                    .read(true)
-                    .write(true)
-                    .open(device_path)
-                    .map_err(|x| InitializationError::ErrorUSB(IoError { error: x }))?;
-
-                let raw_file_descriptor = file.as_raw_fd();
-
-                let mut ioctl_info = IoctlInfo {
-                    vendor: 0,
-                    product: 0,
-                    serial: [0; 9],
-                    revision: 0,
-                    speed: 0,
-                    power: 0,
-                    interface: 0,
-                    packet_size: 0,
+                let iowarrior_info = match open_device_info(device_path)? {
+                    None => continue,
+                    Some(x) => x,
                 };
 
-                match unsafe { ioctl_info_iowarrior(raw_file_descriptor, &mut ioctl_info) } {
-                    Ok(_) => {}
-                    Err(_) => {
-                        return Err(InitializationError::InternalError(
-                            "Error getting device list.".to_owned(),
-                        ))
-                    }
-                }
-
-                if ioctl_info.vendor != VENDOR_IDENTIFIER {
-                    continue;
-                }
-
-                let device_serial = get_serial_number(&ioctl_info)?;
-
-                if device_serial.is_empty() {
-                    continue;
-                }
-
-                let device_type =
-                    match IOWarriorType::from_device_product_id(ioctl_info.product as u16) {
-                        None => continue,
-                        Some(x) => x,
-                    };
-
-                let usb_pipe = USBPipe {
-                    file,
-                    interface: ioctl_info.interface as u8,
-                };
-
-                device_list.push(IOWarriorInfo {
-                    device_revision: ioctl_info.revision as u16,
-                    device_serial,
-                    device_type,
-                    usb_pipe,
-                });
+                device_list.push(iowarrior_info);
             }
         }
     }
@@ -226,14 +285,209 @@ fn get_device_list() -> Result<Vec<IOWarriorInfo>, InitializationError> {
     Ok(device_list)
 }
 
-fn get_serial_number(ioctl_info: &IoctlInfo) -> Result<String, InitializationError> {
+fn open_device_info(device_path: &str) -> Result<Option<IOWarriorInfo>, InitializationError> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(device_path)
+        .map_err(|x| InitializationError::from_open_error(device_path, x))?;
+
+    let raw_file_descriptor = file.as_raw_fd();
+
+    let mut ioctl_info = IoctlInfo {
+        vendor: 0,
+        product: 0,
+        serial: [0; 9],
+        revision: 0,
+        speed: 0,
+        power: 0,
+        interface: 0,
+        packet_size: 0,
+    };
+
+    match unsafe { ioctl_info_iowarrior(raw_file_descriptor, &mut ioctl_info) } {
+        Ok(_) => {}
+        Err(errno) => {
+            return Err(InitializationError::IoctlFailed {
+                path: device_path.to_owned(),
+                errno: errno as i32,
+            })
+        }
+    }
+
+    if ioctl_info.vendor != VENDOR_IDENTIFIER {
+        return Ok(None);
+    }
+
+    let device_serial = get_serial_number(&ioctl_info, device_path)?;
+
+    let device_type = match IOWarriorType::from_device_product_id(ioctl_info.product as u16) {
+        None => return Ok(None),
+        Some(x) => x,
+    };
+
+    let usb_pipe = USBPipe {
+        file,
+        interface: ioctl_info.interface as u8,
+    };
+
+    Ok(Some(IOWarriorInfo {
+        device_path: device_path.to_owned(),
+        device_revision: ioctl_info.revision as u16,
+        device_serial,
+        device_type,
+        usb_pipe,
+        usb_speed: get_usb_speed(ioctl_info.speed),
+        max_power: get_max_power(ioctl_info.power),
+        packet_size: ioctl_info.packet_size as u16,
+    }))
+}
+
+fn get_interface_list() -> Result<Vec<InterfaceInfo>, InitializationError> {
+    let mut interface_list: Vec<InterfaceInfo> = Vec::new();
+
+    for glob_result in glob_paths(&format!("{}/iowarrior*", USBMISC_CLASS_PATH))? {
+        let entry = glob_result.map_err(to_enumeration_error)?;
+
+        let device_name = match entry.file_name().and_then(|x| x.to_str()) {
+            None => continue,
+            Some(x) => x.to_owned(),
+        };
+
+        let interface_path = match entry.join("device").canonicalize() {
+            Err(_) => continue,
+            Ok(x) => x,
+        };
+
+        let interface = match read_hex_attribute(&interface_path, "bInterfaceNumber") {
+            None => continue,
+            Some(x) => x as u8,
+        };
+
+        let usb_device_path = match interface_path.parent() {
+            None => continue,
+            Some(x) => x.to_path_buf(),
+        };
+
+        interface_list.push(InterfaceInfo {
+            usb_device_path,
+            interface,
+            device_path: format!("/dev/usb/{}", device_name),
+        });
+    }
+
+    Ok(interface_list)
+}
+
+fn get_iowarrior_descriptor(
+    usb_device_path: &Path,
+    interface_infos: &[InterfaceInfo],
+) -> Option<IOWarriorDescriptor> {
+    let vendor_id = read_hex_attribute(usb_device_path, "idVendor")?;
+
+    if i32::from(vendor_id) != VENDOR_IDENTIFIER {
+        return None;
+    }
+
+    let product_id = read_hex_attribute(usb_device_path, "idProduct")?;
+    let device_type = IOWarriorType::from_device_product_id(product_id)?;
+    let device_revision = read_hex_attribute(usb_device_path, "bcdDevice")?;
+
+    // Legacy IOWarrior40s have no serial number.
+    let serial_number = std::fs::read_to_string(usb_device_path.join("serial"))
+        .map(|x| String::from(x.trim()))
+        .unwrap_or_default();
+
+    let device_path = interface_infos
+        .iter()
+        .min_by_key(|x| x.interface)?
+        .device_path
+        .clone();
+
+    Some(IOWarriorDescriptor {
+        serial_number,
+        device_type,
+        device_revision,
+        backend: CommunicationBackend::IOCtrl,
+        device_path: DevicePath::Path(device_path),
+        interface_count: interface_infos.len() as u8,
+    })
+}
+
+fn read_hex_attribute(path: &Path, attribute: &str) -> Option<u16> {
+    let value = std::fs::read_to_string(path.join(attribute)).ok()?;
+
+    u16::from_str_radix(value.trim(), 16).ok()
+}
+
+// The driver already reports milliamperes and -1 if the power is unknown.
+fn get_max_power(power: raw::c_int) -> Option<u16> {
+    u16::try_from(power).ok()
+}
+
+fn get_usb_speed(speed: raw::c_int) -> Option<USBSpeed> {
+    match speed {
+        1 => Some(USBSpeed::Low),
+        2 => Some(USBSpeed::Full),
+        3 => Some(USBSpeed::High),
+        5 => Some(USBSpeed::Super),
+        6 => Some(USBSpeed::SuperPlus),
+        _ => None,
+    }
+}
+
+fn get_serial_number(
+    ioctl_info: &IoctlInfo,
+    device_path: &str,
+) -> Result<String, InitializationError> {
     let raw_pointer = ioctl_info.serial.as_ptr();
 
     let cstr = unsafe { CStr::from_ptr(raw_pointer) };
 
-    let str = cstr.to_str().map_err(|x| {
-        InitializationError::InternalError("Error getting serial number.".to_owned())
-    })?;
+    let str = cstr
+        .to_str()
+        .map_err(|x| InitializationError::InvalidSerialNumber {
+            path: device_path.to_owned(),
+        })?;
 
     Ok(String::from(str))
 }
+
+fn glob_paths(pattern: &str) -> Result<glob::Paths, InitializationError> {
+    glob::glob(pattern).map_err(|x| InitializationError::EnumerationFailed {
+        path: pattern.to_owned(),
+        error: io::Error::new(io::ErrorKind::InvalidInput, x),
+    })
+}
+
+fn to_enumeration_error(error: glob::GlobError) -> InitializationError {
+    InitializationError::EnumerationFailed {
+        path: error.path().display().to_string(),
+        error: error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_power_is_reported_in_milliamperes() {
+        assert_eq!(get_max_power(100), Some(100));
+        assert_eq!(get_max_power(500), Some(500));
+        assert_eq!(get_max_power(0), Some(0));
+    }
+
+    #[test]
+    fn unknown_max_power_is_none() {
+        assert_eq!(get_max_power(-1), None);
+    }
+
+    #[test]
+    fn usb_speed_follows_kernel_values() {
+        assert_eq!(get_usb_speed(1), Some(USBSpeed::Low));
+        assert_eq!(get_usb_speed(3), Some(USBSpeed::High));
+        assert_eq!(get_usb_speed(0), None);
+        assert_eq!(get_usb_speed(4), None);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::communication::CommunicationBackend;
+use crate::iowarrior::IOWarriorType;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct IOWarriorDescriptor {
+    pub serial_number: String,
+    pub device_type: IOWarriorType,
+    pub device_revision: u16,
+    pub backend: CommunicationBackend,
+    pub device_path: DevicePath,
+    pub interface_count: u8,
+}
+
+impl fmt::Display for IOWarriorDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum DevicePath {
+    Path(String),
+    Location(String),
+    Index(u32),
+}
+
+impl fmt::Display for DevicePath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
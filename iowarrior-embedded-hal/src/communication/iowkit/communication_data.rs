@@ -1,30 +1,19 @@
-use std::fmt;
-use std::os::raw;
-use std::ptr::NonNull;
+use crate::communication::CancelFlag;
+use iowkit_sys::IowkitDevice;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug)]
-pub struct IowkitData {
-    pub iowkit: iowkit_sys::Iowkit,
-    pub iowkit_handle: NonNull<raw::c_void>,
-}
-
-impl fmt::Display for IowkitData {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-impl Drop for IowkitData {
-    #[inline]
-    fn drop(&mut self) {
-        unsafe { self.iowkit.IowKitCloseDevice(self.iowkit_handle.as_ptr()) }
-    }
+pub struct CommunicationData {
+    pub device: IowkitDevice,
+    pub max_pipe: u8,
+    pub read_timeout: Option<Duration>,
+    pub cancel_data: Arc<CancelData>,
 }
 
 #[derive(Debug)]
-pub struct CommunicationData {
-    pub iowkit_data: Arc<IowkitData>,
-    pub device_handle: NonNull<raw::c_void>,
+pub struct CancelData {
+    pub device: IowkitDevice,
     pub max_pipe: u8,
+    pub cancel_flag: CancelFlag,
 }
@@ -1,75 +1,136 @@
-use crate::communication::CommunicationData;
-use crate::iowarrior::Report;
-use hidapi::HidError;
-
-pub fn write_report(
-    communication_data: &mut CommunicationData,
-    report: &Report,
-) -> Result<(), HidError> {
-    let pipe = u8::min(report.pipe.get_value(), communication_data.max_pipe);
-
-    let written_bytes = unsafe {
-        communication_data.iowkit_data.iowkit.IowKitWrite(
-            communication_data.device_handle.as_ptr(),
-            pipe as iowkit_sys::ULONG,
-            report.buffer.as_ptr() as iowkit_sys::PCHAR,
-            report.buffer.len() as iowkit_sys::ULONG,
-        )
-    } as usize;
-
-    if written_bytes != report.buffer.len() {
-        return Err(HidError::IncompleteSendError {
-            sent: written_bytes,
-            all: report.buffer.len(),
-        });
+use crate::communication::iowkit::{CancelData, CommunicationData};
+use crate::communication::{Canceller, Transport, TransportError, USBInfo};
+use crate::iowarrior::{Pipe, Report};
+use iowkit_sys::IowkitPipe;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+impl Transport for CommunicationData {
+    fn write_report(&mut self, report: &Report) -> Result<(), TransportError> {
+        let pipe = to_iowkit_pipe(report.pipe, self.max_pipe);
+
+        let written_bytes = self.device.write(pipe, report.buffer.as_slice());
+
+        if written_bytes == 0 && is_device_removed() {
+            return Err(TransportError::Disconnected);
+        }
+
+        if written_bytes != report.buffer.len() {
+            return Err(TransportError::ShortWrite {
+                written: written_bytes,
+                expected: report.buffer.len(),
+            });
+        }
+
+        Ok(())
     }
 
-    Ok(())
-}
+    fn read_report_non_blocking(
+        &mut self,
+        mut report: Report,
+    ) -> Result<Option<Report>, TransportError> {
+        let pipe = to_iowkit_pipe(report.pipe, self.max_pipe);
+
+        let read_bytes = self
+            .device
+            .read_non_blocking(pipe, report.buffer.as_mut_slice());
+
+        if read_bytes == 0 && is_device_removed() {
+            return Err(TransportError::Disconnected);
+        }
 
-pub fn read_report_non_blocking(
-    communication_data: &mut CommunicationData,
-    mut report: Report,
-) -> Result<Option<Report>, HidError> {
-    let pipe = u8::min(report.pipe.get_value(), communication_data.max_pipe);
-
-    let read_bytes = unsafe {
-        communication_data.iowkit_data.iowkit.IowKitReadNonBlocking(
-            communication_data.device_handle.as_ptr(),
-            pipe as iowkit_sys::ULONG,
-            report.buffer.as_mut_ptr() as iowkit_sys::PCHAR,
-            report.buffer.len() as iowkit_sys::ULONG,
-        )
-    } as usize;
-
-    if read_bytes != report.buffer.len() {
-        return Ok(None);
+        if read_bytes != report.buffer.len() {
+            return Ok(None);
+        }
+
+        Ok(Some(report))
     }
 
-    Ok(Some(report))
+    fn read_report(&mut self, mut report: Report) -> Result<Report, TransportError> {
+        if self.cancel_data.cancel_flag.take() {
+            return Err(TransportError::Cancelled);
+        }
+
+        let pipe = to_iowkit_pipe(report.pipe, self.max_pipe);
+
+        let read_bytes = self.device.read(pipe, report.buffer.as_mut_slice());
+
+        if read_bytes != report.buffer.len() && self.cancel_data.cancel_flag.take() {
+            return Err(TransportError::Cancelled);
+        }
+
+        if read_bytes == 0 && is_device_removed() {
+            return Err(TransportError::Disconnected);
+        }
+
+        if read_bytes == 0 && self.read_timeout.is_some() {
+            return Err(TransportError::Timeout);
+        }
+
+        if read_bytes != report.buffer.len() {
+            return Err(TransportError::ShortRead {
+                read: read_bytes,
+                expected: report.buffer.len(),
+            });
+        }
+
+        Ok(report)
+    }
+
+    fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<(), TransportError> {
+        let read_result = self.device.set_read_timeout(read_timeout);
+        let write_result = self.device.set_write_timeout(write_timeout);
+
+        if !read_result || !write_result {
+            return Err(TransportError::Other(
+                "Failed to set iowkit timeout.".to_owned(),
+            ));
+        }
+
+        self.read_timeout = read_timeout;
+
+        Ok(())
+    }
+
+    fn get_canceller(&self) -> Arc<dyn Canceller> {
+        self.cancel_data.clone()
+    }
+
+    fn get_usb_info(&self) -> USBInfo {
+        USBInfo::default()
+    }
 }
 
-pub fn read_report(
-    communication_data: &mut CommunicationData,
-    mut report: Report,
-) -> Result<Report, HidError> {
-    let pipe = u8::min(report.pipe.get_value(), communication_data.max_pipe);
-
-    let read_bytes = unsafe {
-        communication_data.iowkit_data.iowkit.IowKitRead(
-            communication_data.device_handle.as_ptr(),
-            pipe as iowkit_sys::ULONG,
-            report.buffer.as_mut_ptr() as iowkit_sys::PCHAR,
-            report.buffer.len() as iowkit_sys::ULONG,
-        )
-    } as usize;
-
-    if read_bytes != report.buffer.len() {
-        return Err(HidError::IncompleteSendError {
-            sent: read_bytes,
-            all: report.buffer.len(),
-        });
+impl Canceller for CancelData {
+    fn cancel(&self) {
+        self.cancel_flag.set();
+
+        for pipe in 0..=self.max_pipe {
+            if let Some(pipe) = IowkitPipe::from_value(pipe as iowkit_sys::ULONG) {
+                self.device.cancel_io(pipe);
+            }
+        }
     }
+}
 
-    Ok(report)
+// iowkit reports a failed transfer as zero bytes, the OS error tells whether the device is gone.
+fn is_device_removed() -> bool {
+    matches!(
+        TransportError::from(io::Error::last_os_error()),
+        TransportError::Disconnected
+    )
+}
+
+fn to_iowkit_pipe(pipe: Pipe, max_pipe: u8) -> IowkitPipe {
+    match u8::min(pipe.get_value(), max_pipe) {
+        0 => IowkitPipe::IOPins,
+        1 => IowkitPipe::SpecialMode,
+        2 => IowkitPipe::I2CMode,
+        _ => IowkitPipe::ADCMode,
+    }
 }
@@ -1,94 +1,258 @@
-use crate::communication::IowkitData;
-use crate::communication::{CommunicationData, InitializationError};
+use crate::communication::iowkit::{CancelData, CommunicationData};
+use crate::communication::{
+    CancelFlag, CommunicationBackend, DevicePath, IOWarriorDescriptor, InitializationConfig,
+    InitializationError,
+};
 use crate::iowarrior::{iowarrior_service, IOWarrior, IOWarriorType, Pipe};
-use std::ptr::NonNull;
+use iowkit_sys::{IowkitDevice, IowkitLibrary, IowkitSession, LegacyOpenMode};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::{env, fmt};
 
 #[cfg(target_os = "windows")]
-const IOWKIT: &str = "iowkit.dll";
+const IOWKIT_CANDIDATES: [&str; 1] = ["iowkit.dll"];
 
 #[cfg(target_os = "linux")]
-const IOWKIT: &str = "libiowkit.so";
+const IOWKIT_CANDIDATES: [&str; 2] = ["libiowkit.so", "libiowkit.so.1"];
 
-pub fn get_iowarriors() -> Result<Vec<IOWarrior>, InitializationError> {
-    let iowkit = unsafe { iowkit_sys::Iowkit::new(IOWKIT) }.map_err(|x| {
-        InitializationError::InternalError("Error loading iowkit library.".to_owned())
-    })?;
+const IOWKIT_LIBRARY_PATH_VARIABLE: &str = "IOWKIT_LIBRARY_PATH";
+const IOWKIT_MINIMUM_VERSION: [u32; 2] = [1, 5];
 
-    let iowkit_handle = match NonNull::new(unsafe { iowkit.IowKitOpenDevice() }) {
+#[derive(Debug)]
+struct IOWarriorInfo {
+    device: IowkitDevice,
+    device_type: IOWarriorType,
+    device_revision: u16,
+    device_serial: String,
+}
+
+impl fmt::Display for IOWarriorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+pub fn list_iowarriors(
+    config: &InitializationConfig,
+) -> Result<Vec<IOWarriorDescriptor>, InitializationError> {
+    let iowkit_session = match open_iowkit(config)? {
+        None => return Ok(Vec::<IOWarriorDescriptor>::with_capacity(0)),
+        Some(x) => x,
+    };
+
+    let mut vec: Vec<IOWarriorDescriptor> = Vec::new();
+
+    for device in iowkit_session.get_devices() {
+        let iowarrior_info = match get_iowarrior_info(device)? {
+            None => continue,
+            Some(x) => x,
+        };
+
+        vec.push(IOWarriorDescriptor {
+            serial_number: iowarrior_info.device_serial,
+            device_type: iowarrior_info.device_type,
+            device_revision: iowarrior_info.device_revision,
+            backend: CommunicationBackend::IOWKit,
+            device_path: DevicePath::Index(iowarrior_info.device.get_device_number() as u32),
+            interface_count: get_max_pipe(iowarrior_info.device_type) + 1,
+        });
+    }
+
+    Ok(vec)
+}
+
+pub fn get_iowarriors(
+    config: &InitializationConfig,
+) -> Result<Vec<IOWarrior>, InitializationError> {
+    let iowkit_session = match open_iowkit(config)? {
         None => return Ok(Vec::<IOWarrior>::with_capacity(0)),
         Some(x) => x,
     };
 
-    let device_count = unsafe { iowkit.IowKitGetNumDevs() };
     let mut vec: Vec<IOWarrior> = Vec::new();
 
-    let iowkit_data = Arc::new(IowkitData {
-        iowkit,
-        iowkit_handle,
-    });
-
-    for index in 0..device_count {
-        let device_handle =
-            match NonNull::new(unsafe { iowkit_data.iowkit.IowKitGetDeviceHandle(index + 1) }) {
-                None => continue,
-                Some(x) => x,
-            };
-
-        let device_product_id = unsafe {
-            iowkit_data
-                .iowkit
-                .IowKitGetProductId(device_handle.as_ptr())
-        } as u16;
-        let device_revision =
-            unsafe { iowkit_data.iowkit.IowKitGetRevision(device_handle.as_ptr()) } as u16;
-
-        let device_type = match IOWarriorType::from_device_product_id(device_product_id) {
+    for device in iowkit_session.get_devices() {
+        let iowarrior_info = match get_iowarrior_info(device)? {
             None => continue,
             Some(x) => x,
         };
 
-        if device_type == IOWarriorType::IOWarrior40 && device_revision < 0x1010 {
-            continue;
+        let iowarrior = get_iowarrior_internal(iowarrior_info, config)?;
+
+        vec.push(iowarrior);
+    }
+
+    Ok(vec)
+}
+
+pub fn get_iowarrior_by_path(
+    device_path: &DevicePath,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let device_number = match device_path {
+        DevicePath::Index(x) => *x as iowkit_sys::ULONG,
+        DevicePath::Path(_) | DevicePath::Location(_) => {
+            return Err(InitializationError::NotFound(device_path.to_string()))
         }
+    };
+
+    let iowkit_session = match open_iowkit(config)? {
+        None => return Err(InitializationError::NotFound(device_path.to_string())),
+        Some(x) => x,
+    };
+
+    let device = match iowkit_session.get_device(device_number) {
+        None => return Err(InitializationError::NotFound(device_path.to_string())),
+        Some(x) => x,
+    };
+
+    match get_iowarrior_info(device)? {
+        None => Err(InitializationError::NotFound(device_path.to_string())),
+        Some(iowarrior_info) => get_iowarrior_internal(iowarrior_info, config),
+    }
+}
+
+fn get_iowarrior_internal(
+    iowarrior_info: IOWarriorInfo,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let communication_data = CommunicationData {
+        device: iowarrior_info.device.clone(),
+        max_pipe: get_max_pipe(iowarrior_info.device_type),
+        read_timeout: None,
+        cancel_data: Arc::new(CancelData {
+            device: iowarrior_info.device,
+            max_pipe: get_max_pipe(iowarrior_info.device_type),
+            cancel_flag: CancelFlag::default(),
+        }),
+    };
+
+    iowarrior_service::create_iowarrior(
+        iowarrior_info.device_type,
+        iowarrior_info.device_revision,
+        iowarrior_info.device_serial,
+        Box::new(communication_data),
+        config,
+    )
+}
+
+fn open_iowkit(
+    config: &InitializationConfig,
+) -> Result<Option<Arc<IowkitSession>>, InitializationError> {
+    let iowkit_library = load_iowkit(config)?;
+
+    check_iowkit_version(&iowkit_library)?;
+
+    Ok(iowkit_library.open())
+}
+
+fn load_iowkit(config: &InitializationConfig) -> Result<Arc<IowkitLibrary>, InitializationError> {
+    let candidates = get_iowkit_candidates(config);
 
-        let device_serial = {
-            let mut raw_device_serial_number = [0u16; 9];
-
-            let device_serial_number_result = unsafe {
-                iowkit_data.iowkit.IowKitGetSerialNumber(
-                    device_handle.as_ptr(),
-                    raw_device_serial_number.as_mut_ptr(),
-                )
-            };
-
-            if device_serial_number_result > 0i32 {
-                String::from_utf16_lossy(&raw_device_serial_number)
-            } else {
-                return Err(InitializationError::InternalError(
-                    "Failed to get serial number.".to_owned(),
-                ));
+    for path in &candidates {
+        // Legacy IOWarrior40s only need their IO pins interface, special mode is not supported.
+        match unsafe { IowkitLibrary::new(path, LegacyOpenMode::Simple) } {
+            Ok(iowkit_library) => return Ok(iowkit_library),
+            // A library file that exists but fails to load is worth reporting on its own.
+            Err(error) if path.is_file() => {
+                return Err(InitializationError::IowkitLibraryNotLoaded {
+                    path: path.clone(),
+                    error,
+                })
             }
-        };
+            Err(_) => continue,
+        }
+    }
 
-        let communication_data = CommunicationData {
-            iowkit_data: iowkit_data.clone(),
-            device_handle,
-            max_pipe: get_max_pipe(device_type),
-        };
+    Err(InitializationError::IowkitLibraryNotFound { candidates })
+}
 
-        let iowarrior = iowarrior_service::create_iowarrior(
-            device_type,
-            device_revision,
-            device_serial,
-            communication_data,
-        )
-        .map_err(|x| InitializationError::ErrorUSB(x))?;
+fn get_iowkit_candidates(config: &InitializationConfig) -> Vec<PathBuf> {
+    if let Some(path) = &config.iowkit_library_path {
+        return vec![path.clone()];
+    }
 
-        vec.push(iowarrior);
+    if let Some(path) = env::var_os(IOWKIT_LIBRARY_PATH_VARIABLE).filter(|x| !x.is_empty()) {
+        return vec![PathBuf::from(path)];
     }
 
-    Ok(vec)
+    let executable_directory = env::current_exe()
+        .ok()
+        .and_then(|x| x.parent().map(Path::to_path_buf));
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    if let Some(directory) = executable_directory {
+        candidates.extend(
+            IOWKIT_CANDIDATES
+                .iter()
+                .map(|x| directory.join(x))
+                .filter(|x| x.is_file()),
+        );
+    }
+
+    candidates.extend(IOWKIT_CANDIDATES.iter().map(PathBuf::from));
+
+    candidates
+}
+
+fn check_iowkit_version(iowkit_library: &IowkitLibrary) -> Result<(), InitializationError> {
+    let version = iowkit_library.get_version();
+
+    match parse_iowkit_version(version) {
+        Some(x) if x >= IOWKIT_MINIMUM_VERSION => Ok(()),
+        _ => Err(InitializationError::IowkitVersionNotSupported {
+            version: version.to_owned(),
+            minimum_version: format!(
+                "{}.{}",
+                IOWKIT_MINIMUM_VERSION[0], IOWKIT_MINIMUM_VERSION[1]
+            ),
+        }),
+    }
+}
+
+fn parse_iowkit_version(version: &str) -> Option<[u32; 2]> {
+    let number = version
+        .split_whitespace()
+        .last()?
+        .trim_start_matches(['V', 'v']);
+
+    let mut parts = number.split('.').map(|x| x.parse::<u32>());
+
+    let major = parts.next()?.ok()?;
+    let minor = match parts.next() {
+        None => 0,
+        Some(x) => x.ok()?,
+    };
+
+    Some([major, minor])
+}
+
+fn get_iowarrior_info(device: IowkitDevice) -> Result<Option<IOWarriorInfo>, InitializationError> {
+    let device_product_id = device.get_product_id();
+    let device_revision = device.get_revision();
+
+    let device_type = match IOWarriorType::from_device_product_id(device_product_id) {
+        None => return Ok(None),
+        Some(x) => x,
+    };
+
+    let device_serial = match device.get_serial_number() {
+        Some(x) => x,
+        None if device_revision < iowarrior_service::NON_LEGACY_REVISION => String::new(),
+        None => {
+            return Err(InitializationError::InvalidSerialNumber {
+                path: DevicePath::Index(device.get_device_number() as u32).to_string(),
+            })
+        }
+    };
+
+    Ok(Some(IOWarriorInfo {
+        device,
+        device_type,
+        device_revision,
+        device_serial,
+    }))
 }
 
 fn get_max_pipe(device_type: IOWarriorType) -> u8 {
@@ -105,13 +269,62 @@ fn get_max_pipe(device_type: IOWarriorType) -> u8 {
     }
 }
 
-pub fn get_iowarrior(serial_number: &str) -> Result<IOWarrior, InitializationError> {
-    match get_iowarriors()?
-        .into_iter()
-        .filter(|x| x.get_serial_number() == serial_number)
-        .next()
-    {
-        None => Err(InitializationError::NotFound(String::from(serial_number))),
-        Some(x) => Ok(x),
+pub fn get_iowarrior(
+    serial_number: &str,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let iowkit_session = match open_iowkit(config)? {
+        None => return Err(InitializationError::NotFound(String::from(serial_number))),
+        Some(x) => x,
+    };
+
+    for device in iowkit_session.get_devices() {
+        match get_iowarrior_info(device)? {
+            Some(iowarrior_info) if iowarrior_info.device_serial == serial_number => {
+                return get_iowarrior_internal(iowarrior_info, config);
+            }
+            _ => continue,
+        }
+    }
+
+    Err(InitializationError::NotFound(String::from(serial_number)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_iowkit_version_reads_last_word() {
+        assert_eq!(parse_iowkit_version("IO-Warrior Kit V1.5"), Some([1, 5]));
+        assert_eq!(parse_iowkit_version("IOWKit v1.7"), Some([1, 7]));
+        assert_eq!(parse_iowkit_version("V2"), Some([2, 0]));
+        assert_eq!(parse_iowkit_version("1.10"), Some([1, 10]));
+    }
+
+    #[test]
+    fn parse_iowkit_version_rejects_invalid_versions() {
+        for version in ["", "IO-Warrior Kit", "IO-Warrior Kit Vx.5", "V1.x"] {
+            assert_eq!(parse_iowkit_version(version), None, "{}", version);
+        }
+    }
+
+    #[test]
+    fn iowkit_versions_compare_numerically() {
+        assert!(parse_iowkit_version("V1.10").unwrap() >= IOWKIT_MINIMUM_VERSION);
+        assert!(parse_iowkit_version("V1.4").unwrap() < IOWKIT_MINIMUM_VERSION);
+    }
+
+    #[test]
+    fn configured_iowkit_path_is_the_only_candidate() {
+        let config = InitializationConfig {
+            iowkit_library_path: Some(PathBuf::from("/opt/iowkit/libiowkit.so")),
+            ..InitializationConfig::default()
+        };
+
+        assert_eq!(
+            get_iowkit_candidates(&config),
+            vec![PathBuf::from("/opt/iowkit/libiowkit.so")]
+        );
     }
 }
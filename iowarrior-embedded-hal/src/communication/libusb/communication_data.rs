@@ -0,0 +1,56 @@
+use crate::communication::CancelFlag;
+use rusb::{DeviceHandle, GlobalContext, Speed};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct USBPipe {
+    pub interface: u8,
+    pub endpoint_in: u8,
+    pub endpoint_out: Option<u8>,
+    pub packet_size: u16,
+}
+
+impl fmt::Display for USBPipe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum USBPipes {
+    Standard {
+        pipe_0: USBPipe,
+        pipe_1: USBPipe,
+    },
+    Extended {
+        pipe_0: USBPipe,
+        pipe_1: USBPipe,
+        pipe_2: USBPipe,
+        pipe_3: USBPipe,
+    },
+}
+
+impl fmt::Display for USBPipes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug)]
+pub struct CommunicationData {
+    pub device_handle: DeviceHandle<GlobalContext>,
+    pub usb_pipes: USBPipes,
+    pub usb_speed: Speed,
+    pub max_power: u16,
+    pub read_timeout: Duration,
+    pub write_timeout: Duration,
+    pub cancel_flag: Arc<CancelFlag>,
+}
+
+impl fmt::Display for CommunicationData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
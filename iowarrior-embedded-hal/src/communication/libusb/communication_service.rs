@@ -0,0 +1,208 @@
+use crate::communication::libusb::{CommunicationData, USBPipe, USBPipes};
+use crate::communication::{
+    next_poll_interval, Canceller, Transport, TransportError, USBInfo, USBSpeed,
+};
+use crate::iowarrior::{Pipe, Report};
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const REQUEST_TYPE_SET_REPORT: u8 = 0x21;
+const REQUEST_SET_REPORT: u8 = 0x09;
+const OUTPUT_REPORT: u16 = 0x0200;
+const NON_BLOCKING_TIMEOUT: Duration = Duration::from_millis(1);
+
+impl Transport for CommunicationData {
+    fn write_report(&mut self, report: &Report) -> Result<(), TransportError> {
+        let usb_pipe = pipe_to_usb_pipe(&self.usb_pipes, report.pipe);
+        let buffer = report_to_usb_buffer(report);
+
+        let bytes_written = match usb_pipe.endpoint_out {
+            Some(endpoint_out) => {
+                self.device_handle
+                    .write_interrupt(endpoint_out, buffer, self.write_timeout)
+            }
+            None => self.device_handle.write_control(
+                REQUEST_TYPE_SET_REPORT,
+                REQUEST_SET_REPORT,
+                OUTPUT_REPORT,
+                usb_pipe.interface as u16,
+                buffer,
+                self.write_timeout,
+            ),
+        };
+
+        let bytes_written = match bytes_written {
+            Ok(x) => x,
+            Err(rusb::Error::Pipe) => 0,
+            Err(error) => return Err(to_transport_error(error)),
+        };
+
+        if bytes_written != buffer.len() {
+            return Err(TransportError::ShortWrite {
+                written: bytes_written,
+                expected: buffer.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn read_report_non_blocking(
+        &mut self,
+        mut report: Report,
+    ) -> Result<Option<Report>, TransportError> {
+        match read_interrupt(self, &mut report, NON_BLOCKING_TIMEOUT)? {
+            true => Ok(Some(report)),
+            false => Ok(None),
+        }
+    }
+
+    fn read_report(&mut self, mut report: Report) -> Result<Report, TransportError> {
+        let deadline = match self.read_timeout.is_zero() {
+            true => None,
+            false => Some(Instant::now() + self.read_timeout),
+        };
+
+        loop {
+            if self.cancel_flag.take() {
+                return Err(TransportError::Cancelled);
+            }
+
+            let timeout = next_poll_interval(deadline)?;
+
+            if read_interrupt(self, &mut report, timeout)? {
+                return Ok(report);
+            }
+        }
+    }
+
+    fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<(), TransportError> {
+        self.read_timeout = read_timeout.unwrap_or(Duration::ZERO);
+        self.write_timeout = write_timeout.unwrap_or(Duration::ZERO);
+
+        Ok(())
+    }
+
+    fn get_canceller(&self) -> Arc<dyn Canceller> {
+        self.cancel_flag.clone()
+    }
+
+    fn get_usb_info(&self) -> USBInfo {
+        USBInfo {
+            speed: get_usb_speed(self.usb_speed),
+            max_power: Some(self.max_power),
+            packet_sizes: match self.usb_pipes {
+                USBPipes::Standard { pipe_0, pipe_1 } => {
+                    vec![pipe_0.packet_size, pipe_1.packet_size]
+                }
+                USBPipes::Extended {
+                    pipe_0,
+                    pipe_1,
+                    pipe_2,
+                    pipe_3,
+                } => vec![
+                    pipe_0.packet_size,
+                    pipe_1.packet_size,
+                    pipe_2.packet_size,
+                    pipe_3.packet_size,
+                ],
+            },
+        }
+    }
+}
+
+fn read_interrupt(
+    communication_data: &mut CommunicationData,
+    report: &mut Report,
+    timeout: Duration,
+) -> Result<bool, TransportError> {
+    let usb_pipe = pipe_to_usb_pipe(&communication_data.usb_pipes, report.pipe);
+
+    let buffer = match report.pipe {
+        Pipe::IOPins => &mut report.buffer[1..],
+        Pipe::SpecialMode | Pipe::I2CMode | Pipe::ADCMode => &mut report.buffer[0..],
+    };
+
+    let bytes_read =
+        match communication_data
+            .device_handle
+            .read_interrupt(usb_pipe.endpoint_in, buffer, timeout)
+        {
+            Ok(x) => x,
+            Err(rusb::Error::Timeout) => return Ok(false),
+            Err(error) => return Err(to_transport_error(error)),
+        };
+
+    if bytes_read != buffer.len() {
+        return Err(TransportError::ShortRead {
+            read: bytes_read,
+            expected: buffer.len(),
+        });
+    }
+
+    Ok(true)
+}
+
+fn report_to_usb_buffer(report: &Report) -> &[u8] {
+    match report.pipe {
+        Pipe::IOPins => &report.buffer[1..],
+        Pipe::SpecialMode | Pipe::I2CMode | Pipe::ADCMode => &report.buffer[0..],
+    }
+}
+
+fn pipe_to_usb_pipe(usb_pipes: &USBPipes, pipe: Pipe) -> USBPipe {
+    match *usb_pipes {
+        USBPipes::Standard { pipe_0, pipe_1 } => match pipe {
+            Pipe::IOPins => pipe_0,
+            Pipe::SpecialMode | Pipe::I2CMode | Pipe::ADCMode => pipe_1,
+        },
+        USBPipes::Extended {
+            pipe_0,
+            pipe_1,
+            pipe_2,
+            pipe_3,
+        } => match pipe {
+            Pipe::IOPins => pipe_0,
+            Pipe::SpecialMode => pipe_1,
+            Pipe::I2CMode => pipe_2,
+            Pipe::ADCMode => pipe_3,
+        },
+    }
+}
+
+fn get_usb_speed(speed: rusb::Speed) -> Option<USBSpeed> {
+    match speed {
+        rusb::Speed::Low => Some(USBSpeed::Low),
+        rusb::Speed::Full => Some(USBSpeed::Full),
+        rusb::Speed::High => Some(USBSpeed::High),
+        rusb::Speed::Super => Some(USBSpeed::Super),
+        rusb::Speed::SuperPlus => Some(USBSpeed::SuperPlus),
+        _ => None,
+    }
+}
+
+pub fn to_transport_error(error: rusb::Error) -> TransportError {
+    let kind = match error {
+        rusb::Error::Io => io::ErrorKind::Other,
+        rusb::Error::InvalidParam => io::ErrorKind::InvalidInput,
+        rusb::Error::Access => io::ErrorKind::PermissionDenied,
+        rusb::Error::NoDevice => io::ErrorKind::NotConnected,
+        rusb::Error::NotFound => io::ErrorKind::NotFound,
+        rusb::Error::Busy => io::ErrorKind::ResourceBusy,
+        rusb::Error::Timeout => io::ErrorKind::TimedOut,
+        rusb::Error::Overflow => io::ErrorKind::InvalidData,
+        rusb::Error::Pipe => io::ErrorKind::BrokenPipe,
+        rusb::Error::Interrupted => io::ErrorKind::Interrupted,
+        rusb::Error::NoMem => io::ErrorKind::OutOfMemory,
+        rusb::Error::NotSupported => io::ErrorKind::Unsupported,
+        rusb::Error::BadDescriptor => io::ErrorKind::InvalidData,
+        rusb::Error::Other => io::ErrorKind::Other,
+    };
+
+    TransportError::from(io::Error::new(kind, error))
+}
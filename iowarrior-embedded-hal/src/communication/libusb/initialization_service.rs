@@ -0,0 +1,335 @@
+use crate::communication::libusb::communication_service::to_transport_error;
+use crate::communication::libusb::{CommunicationData, USBPipe, USBPipes};
+use crate::communication::{
+    CancelFlag, CommunicationBackend, DevicePath, IOWarriorDescriptor, InitializationConfig,
+    InitializationError,
+};
+use crate::iowarrior::{iowarrior_service, IOWarrior, IOWarriorType};
+use rusb::{Device, DeviceHandle, Direction, GlobalContext, TransferType, UsbContext};
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+const VENDOR_IDENTIFIER: u16 = 1984;
+
+pub fn list_iowarriors() -> Result<Vec<IOWarriorDescriptor>, InitializationError> {
+    let mut vec: Vec<IOWarriorDescriptor> = Vec::new();
+
+    for device in get_device_list()? {
+        // Devices that cannot be opened, e.g. because of missing permissions, are skipped.
+        let (device_handle, device_serial) = match open_device_with_serial(&device) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+
+        let device_descriptor = device
+            .device_descriptor()
+            .map_err(to_initialization_error)?;
+
+        let device_type =
+            match IOWarriorType::from_device_product_id(device_descriptor.product_id()) {
+                None => continue,
+                Some(x) => x,
+            };
+
+        let config_descriptor = device
+            .active_config_descriptor()
+            .map_err(to_initialization_error)?;
+
+        vec.push(IOWarriorDescriptor {
+            serial_number: device_serial,
+            device_type,
+            device_revision: get_revision(device_descriptor.device_version()),
+            backend: CommunicationBackend::LibUSB,
+            device_path: DevicePath::Location(get_location(&device)?),
+            interface_count: config_descriptor.num_interfaces(),
+        });
+    }
+
+    vec.sort();
+
+    Ok(vec)
+}
+
+pub fn get_iowarriors(
+    config: &InitializationConfig,
+) -> Result<Vec<IOWarrior>, InitializationError> {
+    let mut vec: Vec<IOWarrior> = Vec::new();
+
+    for device in get_device_list()? {
+        let (device_handle, device_serial) = match open_device_with_serial(&device) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+
+        let iowarrior = get_iowarrior_internal(device, device_handle, device_serial, config)?;
+
+        vec.push(iowarrior);
+    }
+
+    Ok(vec)
+}
+
+pub fn get_iowarrior(
+    serial_number: &str,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let mut result = Err(InitializationError::NotFound(String::from(serial_number)));
+
+    for device in get_device_list()? {
+        // The error of a device that cannot be opened is kept, as it may be the one looked for.
+        let (device_handle, device_serial) = match open_device_with_serial(&device) {
+            Ok(x) => x,
+            Err(error) => {
+                result = Err(error);
+                continue;
+            }
+        };
+
+        if device_serial == serial_number {
+            return get_iowarrior_internal(device, device_handle, device_serial, config);
+        }
+    }
+
+    result
+}
+
+pub fn get_iowarrior_by_path(
+    device_path: &DevicePath,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let location = match device_path {
+        DevicePath::Location(x) => x,
+        DevicePath::Path(_) | DevicePath::Index(_) => {
+            return Err(InitializationError::NotFound(device_path.to_string()))
+        }
+    };
+
+    for device in get_device_list()? {
+        if get_location(&device)? == *location {
+            let (device_handle, device_serial) = open_device_with_serial(&device)?;
+
+            return get_iowarrior_internal(device, device_handle, device_serial, config);
+        }
+    }
+
+    Err(InitializationError::NotFound(device_path.to_string()))
+}
+
+fn get_iowarrior_internal(
+    device: Device<GlobalContext>,
+    device_handle: DeviceHandle<GlobalContext>,
+    device_serial: String,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let device_descriptor = device
+        .device_descriptor()
+        .map_err(to_initialization_error)?;
+
+    let device_type = match IOWarriorType::from_device_product_id(device_descriptor.product_id()) {
+        None => return Err(InitializationError::NotFound(device_serial)),
+        Some(x) => x,
+    };
+
+    let device_revision = get_revision(device_descriptor.device_version());
+
+    let config_descriptor = device
+        .active_config_descriptor()
+        .map_err(to_initialization_error)?;
+
+    let usb_pipes = get_usb_pipes(device_type, &config_descriptor, &get_location(&device)?)?;
+
+    match device_handle.set_auto_detach_kernel_driver(true) {
+        Ok(_) | Err(rusb::Error::NotSupported) => {}
+        Err(x) => return Err(to_initialization_error(x)),
+    }
+
+    for interface in 0..config_descriptor.num_interfaces() {
+        device_handle
+            .claim_interface(interface)
+            .map_err(to_initialization_error)?;
+    }
+
+    let communication_data = CommunicationData {
+        device_handle,
+        usb_pipes,
+        usb_speed: device.speed(),
+        max_power: config_descriptor.max_power(),
+        read_timeout: Duration::ZERO,
+        write_timeout: Duration::ZERO,
+        cancel_flag: Arc::new(CancelFlag::default()),
+    };
+
+    iowarrior_service::create_iowarrior(
+        device_type,
+        device_revision,
+        device_serial,
+        Box::new(communication_data),
+        config,
+    )
+}
+
+fn get_device_list() -> Result<Vec<Device<GlobalContext>>, InitializationError> {
+    let device_list = GlobalContext::default()
+        .devices()
+        .map_err(to_initialization_error)?;
+
+    let mut vec: Vec<Device<GlobalContext>> = Vec::new();
+
+    for device in device_list.iter() {
+        let device_descriptor = device
+            .device_descriptor()
+            .map_err(to_initialization_error)?;
+
+        if device_descriptor.vendor_id() == VENDOR_IDENTIFIER
+            && IOWarriorType::from_device_product_id(device_descriptor.product_id()).is_some()
+        {
+            vec.push(device);
+        }
+    }
+
+    Ok(vec)
+}
+
+fn open_device_with_serial(
+    device: &Device<GlobalContext>,
+) -> Result<(DeviceHandle<GlobalContext>, String), InitializationError> {
+    let device_handle = open_device(device)?;
+    let device_serial = get_serial_number(device, &device_handle)?;
+
+    Ok((device_handle, device_serial))
+}
+
+fn open_device(
+    device: &Device<GlobalContext>,
+) -> Result<DeviceHandle<GlobalContext>, InitializationError> {
+    device.open().map_err(|x| match x {
+        rusb::Error::Access => InitializationError::PermissionDenied {
+            path: get_location(device).unwrap_or_default(),
+            error: io::Error::new(io::ErrorKind::PermissionDenied, x),
+        },
+        x => to_initialization_error(x),
+    })
+}
+
+fn get_serial_number(
+    device: &Device<GlobalContext>,
+    device_handle: &DeviceHandle<GlobalContext>,
+) -> Result<String, InitializationError> {
+    let device_descriptor = device
+        .device_descriptor()
+        .map_err(to_initialization_error)?;
+
+    // Legacy IOWarrior40s have no serial number string.
+    if device_descriptor.serial_number_string_index().is_none() {
+        return Ok(String::new());
+    }
+
+    device_handle
+        .read_serial_number_string_ascii(&device_descriptor)
+        .map_err(to_initialization_error)
+}
+
+fn get_location(device: &Device<GlobalContext>) -> Result<String, InitializationError> {
+    let port_numbers = device.port_numbers().map_err(to_initialization_error)?;
+
+    Ok(format!(
+        "{}-{}",
+        device.bus_number(),
+        port_numbers
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>()
+            .join(".")
+    ))
+}
+
+fn get_revision(version: rusb::Version) -> u16 {
+    let major = version.major() as u16;
+    let minor = version.minor() as u16;
+    let sub_minor = version.sub_minor() as u16;
+
+    ((major / 10) << 12) | ((major % 10) << 8) | (minor << 4) | sub_minor
+}
+
+fn get_usb_pipes(
+    device_type: IOWarriorType,
+    config_descriptor: &rusb::ConfigDescriptor,
+    location: &str,
+) -> Result<USBPipes, InitializationError> {
+    Ok(match device_type {
+        IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior28Dongle
+        | IOWarriorType::IOWarrior100 => USBPipes::Extended {
+            pipe_0: get_usb_pipe(config_descriptor, location, 0)?,
+            pipe_1: get_usb_pipe(config_descriptor, location, 1)?,
+            pipe_2: get_usb_pipe(config_descriptor, location, 2)?,
+            pipe_3: get_usb_pipe(config_descriptor, location, 3)?,
+        },
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior28L
+        | IOWarriorType::IOWarrior56
+        | IOWarriorType::IOWarrior56Dongle => USBPipes::Standard {
+            pipe_0: get_usb_pipe(config_descriptor, location, 0)?,
+            pipe_1: get_usb_pipe(config_descriptor, location, 1)?,
+        },
+    })
+}
+
+fn get_usb_pipe(
+    config_descriptor: &rusb::ConfigDescriptor,
+    location: &str,
+    interface_number: u8,
+) -> Result<USBPipe, InitializationError> {
+    let interface_descriptor = match config_descriptor
+        .interfaces()
+        .find(|x| x.number() == interface_number)
+        .and_then(|x| x.descriptors().next())
+    {
+        None => {
+            return Err(InitializationError::MissingInterface {
+                path: location.to_owned(),
+                interface: interface_number,
+            })
+        }
+        Some(x) => x,
+    };
+
+    let mut endpoint_in: Option<(u8, u16)> = None;
+    let mut endpoint_out: Option<u8> = None;
+
+    for endpoint_descriptor in interface_descriptor.endpoint_descriptors() {
+        if endpoint_descriptor.transfer_type() != TransferType::Interrupt {
+            continue;
+        }
+
+        match endpoint_descriptor.direction() {
+            Direction::In => {
+                endpoint_in = Some((
+                    endpoint_descriptor.address(),
+                    endpoint_descriptor.max_packet_size(),
+                ))
+            }
+            Direction::Out => endpoint_out = Some(endpoint_descriptor.address()),
+        }
+    }
+
+    match endpoint_in {
+        None => Err(InitializationError::MissingEndpoint {
+            path: location.to_owned(),
+            interface: interface_number,
+        }),
+        Some((endpoint_in, packet_size)) => Ok(USBPipe {
+            interface: interface_number,
+            endpoint_in,
+            endpoint_out,
+            packet_size,
+        }),
+    }
+}
+
+fn to_initialization_error(error: rusb::Error) -> InitializationError {
+    InitializationError::ErrorUSB(to_transport_error(error))
+}
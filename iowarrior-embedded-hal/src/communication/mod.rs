@@ -1,14 +1,46 @@
+#[cfg(feature = "embedded-hal-async")]
+pub(crate) mod blocking_service;
+#[cfg(feature = "embedded-hal-async")]
+mod blocking_task;
+mod cancel_flag;
+mod device_event;
+mod device_watcher;
+mod device_watcher_config;
+pub(crate) mod device_watcher_service;
+mod disconnected_transport;
+mod initialization_config;
 mod initialization_error;
-#[cfg(feature = "ioctrl")]
-mod ioctrl;
-#[cfg(feature = "iowkit")]
-mod iowkit;
-#[cfg(feature = "usbhid")]
-mod usbhid;
+pub(crate) mod initialization_service;
+#[cfg(all(feature = "ioctrl", target_os = "linux"))]
+pub(crate) mod ioctrl;
+mod iowarrior_descriptor;
+#[cfg(all(feature = "iowkit", any(target_os = "windows", target_os = "linux")))]
+pub(crate) mod iowkit;
+#[cfg(feature = "libusb")]
+pub(crate) mod libusb;
+pub(crate) mod recording;
+pub(crate) mod replay;
+#[cfg(any(feature = "sim", test))]
+pub(crate) mod sim;
+pub(crate) mod trace;
+mod transport;
+mod transport_error;
+mod usb_info;
+#[cfg(all(feature = "usbhid", any(target_os = "windows", target_os = "linux")))]
+pub(crate) mod usbhid;
+
+#[cfg(feature = "embedded-hal-async")]
+pub(crate) use self::blocking_task::*;
+pub use self::cancel_flag::*;
+pub use self::device_event::*;
+pub use self::device_watcher::*;
+pub use self::device_watcher_config::*;
+pub(crate) use self::disconnected_transport::*;
+pub use self::initialization_config::*;
 pub use self::initialization_error::*;
-#[cfg(feature = "ioctrl")]
-pub(crate) use self::ioctrl::*;
-#[cfg(feature = "iowkit")]
-pub(crate) use self::iowkit::*;
-#[cfg(feature = "usbhid")]
-pub(crate) use self::usbhid::*;
+pub use self::iowarrior_descriptor::*;
+#[cfg(any(feature = "sim", test))]
+pub use self::sim::{Simulation, SimulationError};
+pub use self::transport::*;
+pub use self::transport_error::*;
+pub use self::usb_info::*;
@@ -0,0 +1,18 @@
+use crate::communication::Transport;
+use std::fmt;
+use std::fs::File;
+use std::io::LineWriter;
+use std::time::Instant;
+
+#[derive(Debug)]
+pub struct CommunicationData {
+    pub communication_data: Box<dyn Transport>,
+    pub trace_writer: LineWriter<File>,
+    pub start_instant: Instant,
+}
+
+impl fmt::Display for CommunicationData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,112 @@
+use crate::communication::recording::CommunicationData;
+use crate::communication::trace::{trace_service, TraceDirection, TraceEntry};
+use crate::communication::{Canceller, Transport, TransportError, USBInfo};
+use crate::iowarrior::{Pipe, Report};
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+impl Transport for CommunicationData {
+    fn write_report(&mut self, report: &Report) -> Result<(), TransportError> {
+        let result = self.communication_data.write_report(report);
+
+        write_entry(
+            self,
+            TraceDirection::Write,
+            report.pipe,
+            Some(&report.buffer),
+            result.as_ref().err(),
+        );
+
+        result
+    }
+
+    fn read_report_non_blocking(
+        &mut self,
+        report: Report,
+    ) -> Result<Option<Report>, TransportError> {
+        let pipe = report.pipe;
+        let result = self.communication_data.read_report_non_blocking(report);
+
+        match &result {
+            Ok(report) => write_entry(
+                self,
+                TraceDirection::ReadNonBlocking,
+                pipe,
+                report.as_ref().map(|x| &x.buffer),
+                None,
+            ),
+            Err(error) => write_entry(
+                self,
+                TraceDirection::ReadNonBlocking,
+                pipe,
+                None,
+                Some(error),
+            ),
+        }
+
+        result
+    }
+
+    fn read_report(&mut self, report: Report) -> Result<Report, TransportError> {
+        let pipe = report.pipe;
+        let result = self.communication_data.read_report(report);
+
+        match &result {
+            Ok(report) => write_entry(self, TraceDirection::Read, pipe, Some(&report.buffer), None),
+            Err(error) => write_entry(self, TraceDirection::Read, pipe, None, Some(error)),
+        }
+
+        result
+    }
+
+    fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<(), TransportError> {
+        self.communication_data
+            .set_timeouts(read_timeout, write_timeout)
+    }
+
+    fn get_canceller(&self) -> Arc<dyn Canceller> {
+        self.communication_data.get_canceller()
+    }
+
+    fn get_usb_info(&self) -> USBInfo {
+        self.communication_data.get_usb_info()
+    }
+
+    fn replace_transport(
+        &mut self,
+        communication_data: Box<dyn Transport>,
+    ) -> Option<Box<dyn Transport>> {
+        self.communication_data = communication_data;
+        None
+    }
+}
+
+// A failing trace must not change the result of the device operation.
+fn write_entry(
+    communication_data: &mut CommunicationData,
+    direction: TraceDirection,
+    pipe: Pipe,
+    buffer: Option<&Vec<u8>>,
+    error: Option<&TransportError>,
+) {
+    let entry = TraceEntry {
+        timestamp: communication_data.start_instant.elapsed(),
+        direction,
+        pipe,
+        buffer: buffer.cloned(),
+        error: error.map(trace_service::to_trace_error),
+    };
+
+    if let Err(error) = writeln!(
+        communication_data.trace_writer,
+        "{}",
+        trace_service::format_entry(&entry)
+    ) {
+        log::warn!("Failed to write IOWarrior trace entry: {}", error);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::communication::recording::CommunicationData;
+use crate::communication::trace::{trace_service, TraceHeader};
+use crate::communication::{Transport, TransportError};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+pub const TRACE_FILE_EXTENSION: &str = "trace";
+
+static NEXT_TRACE_NUMBER: AtomicUsize = AtomicUsize::new(0);
+
+pub fn start_recording(
+    communication_data: Box<dyn Transport>,
+    header: TraceHeader,
+    trace_directory: &Path,
+) -> Result<Box<dyn Transport>, TransportError> {
+    let file = create_trace_file(trace_directory, &header).map_err(TransportError::IO)?;
+    let mut trace_writer = LineWriter::new(file);
+
+    writeln!(trace_writer, "{}", trace_service::TRACE_SIGNATURE)
+        .and_then(|_| writeln!(trace_writer, "{}", trace_service::format_header(&header)))
+        .map_err(TransportError::IO)?;
+
+    Ok(Box::new(CommunicationData {
+        communication_data,
+        trace_writer,
+        start_instant: Instant::now(),
+    }))
+}
+
+fn create_trace_file(trace_directory: &Path, header: &TraceHeader) -> io::Result<File> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_millis())
+        .unwrap_or_default();
+
+    loop {
+        let trace_number = NEXT_TRACE_NUMBER.fetch_add(1, Ordering::Relaxed);
+        let trace_path = trace_directory.join(get_trace_file_name(header, timestamp, trace_number));
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(trace_path)
+        {
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            result => return result,
+        }
+    }
+}
+
+fn get_trace_file_name(header: &TraceHeader, timestamp: u128, trace_number: usize) -> String {
+    // Serial numbers may be empty or contain characters that are not safe in file names.
+    let device_serial: String = header
+        .device_serial
+        .chars()
+        .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+        .collect();
+
+    format!(
+        "{:?}_{}_{}_{}.{}",
+        header.device_type, device_serial, timestamp, trace_number, TRACE_FILE_EXTENSION
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iowarrior::IOWarriorType;
+
+    fn get_header(device_serial: &str) -> TraceHeader {
+        TraceHeader {
+            device_type: IOWarriorType::IOWarrior40,
+            device_revision: 0x1000,
+            device_serial: String::from(device_serial),
+        }
+    }
+
+    #[test]
+    fn trace_file_name_keeps_serial_and_extension() {
+        assert_eq!(
+            get_trace_file_name(&get_header("0000ABCD"), 42, 7),
+            "IOWarrior40_0000ABCD_42_7.trace"
+        );
+    }
+
+    #[test]
+    fn trace_file_name_escapes_serial() {
+        assert_eq!(
+            get_trace_file_name(&get_header("1.2/3"), 42, 0),
+            "IOWarrior40_1_2_3_42_0.trace"
+        );
+        assert_eq!(
+            get_trace_file_name(&get_header(""), 42, 0),
+            "IOWarrior40__42_0.trace"
+        );
+    }
+
+    #[test]
+    fn trace_files_do_not_overwrite_each_other() {
+        let trace_directory =
+            std::env::temp_dir().join(format!("iowarrior-trace-test-{}", std::process::id()));
+        std::fs::create_dir_all(&trace_directory).unwrap();
+
+        let first = create_trace_file(&trace_directory, &get_header(""));
+        let second = create_trace_file(&trace_directory, &get_header(""));
+        let count = std::fs::read_dir(&trace_directory).unwrap().count();
+        std::fs::remove_dir_all(&trace_directory).unwrap();
+
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn empty_reads_and_timeouts_are_recorded() {
+        use crate::communication::initialization_service::get_replayed_iowarrior;
+        use crate::communication::sim::initialization_service::get_simulated_iowarrior_with_config;
+        use crate::communication::InitializationConfig;
+        use crate::digital::digital_service;
+        use crate::iowarrior::{iowarrior_service, Pipe};
+        use std::time::Duration;
+
+        let trace_directory =
+            std::env::temp_dir().join(format!("iowarrior-trace-reads-test-{}", std::process::id()));
+        std::fs::create_dir_all(&trace_directory).unwrap();
+
+        let config = InitializationConfig {
+            trace_directory: Some(trace_directory.clone()),
+            ..InitializationConfig::default()
+        };
+
+        let read_pin_reports = |iowarrior: &crate::iowarrior::IOWarrior| {
+            let mut mut_data = iowarrior.mut_data_mutex.lock().unwrap();
+
+            digital_service::drain_pin_reports(&iowarrior.data, &mut mut_data)?;
+            iowarrior_service::set_timeouts(&mut mut_data, Some(Duration::from_millis(1)), None)?;
+            iowarrior_service::read_report(
+                &iowarrior.data,
+                &mut mut_data,
+                iowarrior.data.create_report(Pipe::IOPins),
+            )
+            .map(|_| ())
+        };
+
+        let (iowarrior, _) = get_simulated_iowarrior_with_config(
+            IOWarriorType::IOWarrior24,
+            0x1011,
+            "00000001",
+            &config,
+        )
+        .unwrap();
+
+        let recorded = read_pin_reports(&iowarrior);
+        drop(iowarrior);
+
+        let trace_path = std::fs::read_dir(&trace_directory)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let trace = std::fs::read_to_string(&trace_path).unwrap();
+        let replayed = get_replayed_iowarrior(&trace_path).map(|x| read_pin_reports(&x));
+        std::fs::remove_dir_all(&trace_directory).unwrap();
+
+        assert!(
+            trace.lines().any(|x| x.ends_with(" N IOPins -")),
+            "{}",
+            trace
+        );
+        assert!(
+            trace
+                .lines()
+                .any(|x| x.ends_with(" R IOPins - !IO TimedOut")),
+            "{}",
+            trace
+        );
+        assert!(matches!(recorded, Err(TransportError::Timeout)));
+        assert!(matches!(replayed, Ok(Err(TransportError::Timeout))));
+    }
+}
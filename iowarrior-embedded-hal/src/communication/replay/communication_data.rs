@@ -0,0 +1,14 @@
+use crate::communication::trace::TraceEntry;
+use std::collections::VecDeque;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct CommunicationData {
+    pub trace_entries: VecDeque<TraceEntry>,
+}
+
+impl fmt::Display for CommunicationData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
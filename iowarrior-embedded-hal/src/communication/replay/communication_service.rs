@@ -0,0 +1,116 @@
+use crate::communication::replay::CommunicationData;
+use crate::communication::trace::{trace_service, TraceDirection, TraceEntry};
+use crate::communication::{CancelFlag, Canceller, Transport, TransportError, USBInfo};
+use crate::iowarrior::{Pipe, Report};
+use std::sync::Arc;
+use std::time::Duration;
+
+impl Transport for CommunicationData {
+    fn write_report(&mut self, report: &Report) -> Result<(), TransportError> {
+        let entry = next_entry(self, TraceDirection::Write, report.pipe)?;
+
+        if entry.buffer.as_ref() != Some(&report.buffer) {
+            return Err(TransportError::Other(format!(
+                "Replay mismatch, expected: {}",
+                trace_service::format_entry(&entry)
+            )));
+        }
+
+        match &entry.error {
+            None => Ok(()),
+            Some(error) => Err(trace_service::to_transport_error(error)),
+        }
+    }
+
+    fn read_report_non_blocking(
+        &mut self,
+        report: Report,
+    ) -> Result<Option<Report>, TransportError> {
+        // Every read is recorded, an entry is only missing if the replay polls more often.
+        if !is_next_entry(self, TraceDirection::ReadNonBlocking, report.pipe) {
+            return Ok(None);
+        }
+
+        let entry = next_entry(self, TraceDirection::ReadNonBlocking, report.pipe)?;
+
+        match (&entry.buffer, &entry.error) {
+            (None, None) => Ok(None),
+            _ => Ok(Some(entry_to_report(entry, report)?)),
+        }
+    }
+
+    fn read_report(&mut self, report: Report) -> Result<Report, TransportError> {
+        if !is_next_entry(self, TraceDirection::Read, report.pipe) {
+            return Err(TransportError::Timeout);
+        }
+
+        let entry = next_entry(self, TraceDirection::Read, report.pipe)?;
+
+        entry_to_report(entry, report)
+    }
+
+    fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<(), TransportError> {
+        Ok(())
+    }
+
+    fn get_canceller(&self) -> Arc<dyn Canceller> {
+        Arc::new(CancelFlag::default())
+    }
+
+    fn get_usb_info(&self) -> USBInfo {
+        USBInfo::default()
+    }
+}
+
+fn is_next_entry(
+    communication_data: &CommunicationData,
+    direction: TraceDirection,
+    pipe: Pipe,
+) -> bool {
+    communication_data
+        .trace_entries
+        .front()
+        .is_some_and(|x| x.direction == direction && x.pipe == pipe)
+}
+
+fn next_entry(
+    communication_data: &mut CommunicationData,
+    direction: TraceDirection,
+    pipe: Pipe,
+) -> Result<TraceEntry, TransportError> {
+    let entry = match communication_data.trace_entries.pop_front() {
+        None => return Err(TransportError::Other("Replay trace exhausted.".to_owned())),
+        Some(x) => x,
+    };
+
+    if entry.direction != direction || entry.pipe != pipe {
+        return Err(TransportError::Other(format!(
+            "Replay mismatch, expected: {}",
+            trace_service::format_entry(&entry)
+        )));
+    }
+
+    Ok(entry)
+}
+
+fn entry_to_report(entry: TraceEntry, mut report: Report) -> Result<Report, TransportError> {
+    if let Some(error) = &entry.error {
+        return Err(trace_service::to_transport_error(error));
+    }
+
+    match &entry.buffer {
+        Some(buffer) if buffer.len() == report.buffer.len() => {
+            report.buffer.copy_from_slice(buffer);
+
+            Ok(report)
+        }
+        _ => Err(TransportError::Other(format!(
+            "Replay mismatch, expected: {}",
+            trace_service::format_entry(&entry)
+        ))),
+    }
+}
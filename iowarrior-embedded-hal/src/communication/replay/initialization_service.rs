@@ -0,0 +1,62 @@
+use crate::communication::replay::CommunicationData;
+use crate::communication::trace::{trace_service, TraceEntry};
+use crate::communication::{InitializationConfig, InitializationError, TransportError};
+use crate::iowarrior::{iowarrior_service, IOWarrior};
+use std::collections::VecDeque;
+use std::path::Path;
+
+pub fn get_replayed_iowarrior(trace_path: &Path) -> Result<IOWarrior, InitializationError> {
+    let trace = std::fs::read_to_string(trace_path)
+        .map_err(|x| InitializationError::ErrorUSB(TransportError::IO(x)))?;
+
+    let mut lines = trace.lines().enumerate();
+
+    match lines.next() {
+        Some((_, trace_service::TRACE_SIGNATURE)) => {}
+        _ => {
+            return Err(InitializationError::InvalidTrace {
+                path: trace_path.to_path_buf(),
+                line: 1,
+            })
+        }
+    }
+
+    let header = match lines
+        .next()
+        .and_then(|(_, x)| trace_service::parse_header(x))
+    {
+        None => {
+            return Err(InitializationError::InvalidTrace {
+                path: trace_path.to_path_buf(),
+                line: 2,
+            })
+        }
+        Some(x) => x,
+    };
+
+    let mut trace_entries: VecDeque<TraceEntry> = VecDeque::new();
+
+    for (index, line) in lines {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match trace_service::parse_entry(line) {
+            None => {
+                return Err(InitializationError::InvalidTrace {
+                    path: trace_path.to_path_buf(),
+                    line: index + 1,
+                })
+            }
+            Some(x) => trace_entries.push_back(x),
+        }
+    }
+
+    iowarrior_service::create_iowarrior(
+        header.device_type,
+        header.device_revision,
+        header.device_serial,
+        Box::new(CommunicationData { trace_entries }),
+        &InitializationConfig::default(),
+    )
+}
@@ -0,0 +1,5 @@
+mod communication_data;
+mod communication_service;
+pub(crate) mod initialization_service;
+
+pub(crate) use self::communication_data::*;
@@ -0,0 +1,20 @@
+use crate::communication::sim::SimulationData;
+use crate::communication::{CancelFlag, USBInfo};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct CommunicationData {
+    pub simulation_data: Arc<Mutex<SimulationData>>,
+    pub report_condvar: Arc<Condvar>,
+    pub read_timeout: Option<Duration>,
+    pub cancel_flag: Arc<CancelFlag>,
+    pub usb_info: USBInfo,
+}
+
+impl fmt::Display for CommunicationData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,90 @@
+use crate::communication::sim::{firmware_service, CommunicationData};
+use crate::communication::{Canceller, Transport, TransportError, USBInfo};
+use crate::iowarrior::Report;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+impl Transport for CommunicationData {
+    fn write_report(&mut self, report: &Report) -> Result<(), TransportError> {
+        let mut simulation_data = self.simulation_data.lock().unwrap();
+
+        firmware_service::handle_report(&mut simulation_data, report)
+    }
+
+    fn read_report_non_blocking(
+        &mut self,
+        mut report: Report,
+    ) -> Result<Option<Report>, TransportError> {
+        let mut simulation_data = self.simulation_data.lock().unwrap();
+
+        Ok(
+            match firmware_service::next_report(&mut simulation_data, report.pipe) {
+                None => None,
+                Some(buffer) => {
+                    copy_report(&mut report, &buffer)?;
+                    Some(report)
+                }
+            },
+        )
+    }
+
+    fn read_report(&mut self, mut report: Report) -> Result<Report, TransportError> {
+        if self.cancel_flag.take() {
+            return Err(TransportError::Cancelled);
+        }
+
+        let mut simulation_data = self.simulation_data.lock().unwrap();
+
+        // Without a read timeout an empty queue times out at once instead of blocking forever.
+        let deadline = self.read_timeout.map(|x| Instant::now() + x);
+
+        loop {
+            if let Some(buffer) = firmware_service::next_report(&mut simulation_data, report.pipe) {
+                copy_report(&mut report, &buffer)?;
+                return Ok(report);
+            }
+
+            let now = Instant::now();
+
+            match deadline {
+                Some(deadline) if now < deadline => {
+                    simulation_data = self
+                        .report_condvar
+                        .wait_timeout(simulation_data, deadline - now)
+                        .unwrap()
+                        .0;
+                }
+                _ => return Err(TransportError::Timeout),
+            }
+        }
+    }
+
+    fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<(), TransportError> {
+        self.read_timeout = read_timeout;
+        Ok(())
+    }
+
+    fn get_canceller(&self) -> Arc<dyn Canceller> {
+        self.cancel_flag.clone()
+    }
+
+    fn get_usb_info(&self) -> USBInfo {
+        self.usb_info.clone()
+    }
+}
+
+fn copy_report(report: &mut Report, buffer: &[u8]) -> Result<(), TransportError> {
+    if buffer.len() != report.buffer.len() {
+        return Err(TransportError::ShortRead {
+            read: buffer.len(),
+            expected: report.buffer.len(),
+        });
+    }
+
+    report.buffer.copy_from_slice(buffer);
+    Ok(())
+}
@@ -0,0 +1,533 @@
+use crate::bits::Bit::{Bit6, Bit7};
+use crate::bits::Bitmasking;
+use crate::communication::sim::{SimulatedI2CDevice, SimulationData};
+use crate::communication::TransportError;
+use crate::iowarrior::{iowarrior_service, IOWarriorType, Pipe, Report, ReportId};
+use std::collections::HashMap;
+
+pub fn new_simulation_data(device_type: IOWarriorType) -> SimulationData {
+    let standard_report_size = iowarrior_service::get_standard_report_size(device_type);
+    let special_report_size = iowarrior_service::get_special_report_size(device_type);
+
+    SimulationData {
+        device_type,
+        standard_report_size,
+        special_report_size,
+        pins_output: vec![0xFFu8; standard_report_size - 1],
+        pins_external: vec![0xFFu8; standard_report_size - 1],
+        pending_reports: HashMap::new(),
+        i2c_enabled: false,
+        i2c_devices: HashMap::new(),
+        spi_enabled: false,
+        adc_enabled: false,
+        adc_channel_count: 0,
+        adc_packet_number: 0,
+        adc_values: [0u16; 8],
+        pwm_enabled: false,
+        pwm_setup_report: None,
+        pwm_parameters_report: None,
+    }
+}
+
+pub fn handle_report(
+    simulation_data: &mut SimulationData,
+    report: &Report,
+) -> Result<(), TransportError> {
+    let report_size = match report.pipe {
+        Pipe::IOPins => simulation_data.standard_report_size,
+        Pipe::SpecialMode | Pipe::I2CMode | Pipe::ADCMode => simulation_data.special_report_size,
+    };
+
+    if report.buffer.len() != report_size {
+        return Err(TransportError::ShortWrite {
+            written: 0,
+            expected: report.buffer.len(),
+        });
+    }
+
+    if report.pipe == Pipe::IOPins {
+        let ports = report.buffer[1..].to_vec();
+
+        update_pins(simulation_data, |x| x.pins_output = ports);
+        return Ok(());
+    }
+
+    match ReportId::from_u8(report.buffer[0]) {
+        Some(ReportId::GpioSpecialRead) => {
+            let mut buffer = vec![0u8; simulation_data.special_report_size];
+
+            buffer[0] = ReportId::GpioSpecialRead.get_value();
+            buffer[1..simulation_data.standard_report_size]
+                .copy_from_slice(&get_pins_input(simulation_data));
+
+            push_report(simulation_data, report.pipe, buffer);
+        }
+        Some(ReportId::I2cSetup) => {
+            simulation_data.i2c_enabled = report.buffer[1] == 0x01;
+        }
+        Some(ReportId::I2cWrite) => {
+            if simulation_data.i2c_enabled {
+                handle_i2c_write(simulation_data, report)?;
+            }
+        }
+        Some(ReportId::I2cRead) => {
+            if simulation_data.i2c_enabled {
+                handle_i2c_read(simulation_data, report)?;
+            }
+        }
+        Some(ReportId::SpiSetup) => {
+            simulation_data.spi_enabled = report.buffer[1] == 0x01;
+        }
+        Some(ReportId::SpiTransfer) => {
+            if simulation_data.spi_enabled {
+                handle_spi_transfer(simulation_data, report)?;
+            }
+        }
+        Some(ReportId::AdcSetup) => {
+            if simulation_data.device_type == IOWarriorType::IOWarrior28Dongle
+                || simulation_data.device_type == IOWarriorType::IOWarrior56Dongle
+            {
+                return Err(TransportError::ShortWrite {
+                    written: 0,
+                    expected: report.buffer.len(),
+                });
+            }
+
+            simulation_data.adc_enabled = report.buffer[1] == 0x01;
+            simulation_data.adc_channel_count = report.buffer[2];
+            simulation_data.adc_packet_number = 0;
+        }
+        Some(ReportId::PwmSetup) => {
+            simulation_data.pwm_enabled = report.buffer[1] > 0;
+            simulation_data.pwm_setup_report = Some(report.buffer.clone());
+        }
+        Some(ReportId::PwmParameters) => {
+            simulation_data.pwm_parameters_report = Some(report.buffer.clone());
+        }
+        Some(ReportId::AdcRead)
+        | Some(ReportId::TimerSetup)
+        | Some(ReportId::TimerDataA)
+        | Some(ReportId::TimerDataB)
+        | Some(ReportId::GpioReadWrite)
+        | None => { /* Ignored by the firmware. */ }
+    }
+
+    Ok(())
+}
+
+pub fn next_report(simulation_data: &mut SimulationData, pipe: Pipe) -> Option<Vec<u8>> {
+    let physical_pipe = get_physical_pipe(simulation_data.device_type, pipe);
+
+    match simulation_data
+        .pending_reports
+        .get_mut(&physical_pipe)
+        .and_then(|x| x.pop_front())
+    {
+        Some(buffer) => Some(buffer),
+        None => match simulation_data.adc_enabled
+            && physical_pipe == get_physical_pipe(simulation_data.device_type, Pipe::ADCMode)
+        {
+            true => Some(create_adc_report(simulation_data)),
+            false => None,
+        },
+    }
+}
+
+pub fn update_pins<F>(simulation_data: &mut SimulationData, update: F)
+where
+    F: FnOnce(&mut SimulationData),
+{
+    let pins_input_before = get_pins_input(simulation_data);
+
+    update(simulation_data);
+
+    let pins_input_after = get_pins_input(simulation_data);
+
+    if pins_input_before != pins_input_after {
+        let mut buffer = vec![ReportId::GpioReadWrite.get_value()];
+
+        buffer.extend(pins_input_after);
+
+        push_report(simulation_data, Pipe::IOPins, buffer);
+    }
+}
+
+pub fn get_pwm_duty_cycle(simulation_data: &SimulationData, channel: u8) -> Option<u16> {
+    if !simulation_data.pwm_enabled {
+        return None;
+    }
+
+    match simulation_data.device_type {
+        IOWarriorType::IOWarrior56 | IOWarriorType::IOWarrior56Dongle => {
+            let report = simulation_data.pwm_setup_report.as_ref()?;
+
+            match channel <= report[1] {
+                true => Some(read_u16(report, 2 + (channel as usize - 1) * 5 + 2)),
+                false => None,
+            }
+        }
+        IOWarriorType::IOWarrior100 => {
+            let report = simulation_data.pwm_parameters_report.as_ref()?;
+
+            match channel <= report[1] {
+                true => Some(read_u16(report, 6 + (channel as usize - 1) * 2)),
+                false => None,
+            }
+        }
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior28Dongle
+        | IOWarriorType::IOWarrior28L => None,
+    }
+}
+
+fn get_pins_input(simulation_data: &SimulationData) -> Vec<u8> {
+    simulation_data
+        .pins_output
+        .iter()
+        .zip(simulation_data.pins_external.iter())
+        .map(|(output, external)| output & external)
+        .collect()
+}
+
+fn get_physical_pipe(device_type: IOWarriorType, pipe: Pipe) -> Pipe {
+    match device_type {
+        IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior28Dongle
+        | IOWarriorType::IOWarrior100 => pipe,
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior28L
+        | IOWarriorType::IOWarrior56
+        | IOWarriorType::IOWarrior56Dongle => match pipe {
+            Pipe::IOPins => Pipe::IOPins,
+            Pipe::SpecialMode | Pipe::I2CMode | Pipe::ADCMode => Pipe::SpecialMode,
+        },
+    }
+}
+
+fn push_report(simulation_data: &mut SimulationData, pipe: Pipe, mut buffer: Vec<u8>) {
+    let report_size = match pipe {
+        Pipe::IOPins => simulation_data.standard_report_size,
+        Pipe::SpecialMode | Pipe::I2CMode | Pipe::ADCMode => simulation_data.special_report_size,
+    };
+
+    buffer.resize(report_size, 0u8);
+
+    simulation_data
+        .pending_reports
+        .entry(get_physical_pipe(simulation_data.device_type, pipe))
+        .or_default()
+        .push_back(buffer);
+}
+
+fn handle_i2c_write(
+    simulation_data: &mut SimulationData,
+    report: &Report,
+) -> Result<(), TransportError> {
+    let start_byte = report.buffer[1].get_bit(Bit7);
+    let stop_byte = report.buffer[1].get_bit(Bit6);
+    let byte_count = (report.buffer[1] & 0x3F) as usize;
+    let address = report.buffer[2] >> 1;
+
+    if 2 + byte_count > report.buffer.len() {
+        return Err(malformed_report(report));
+    }
+
+    let data = &report.buffer[3..(2 + byte_count).max(3)];
+
+    let acknowledged = match simulation_data.i2c_devices.get_mut(&address) {
+        None => false,
+        Some(i2c_device) => {
+            write_i2c_device(i2c_device, data, start_byte);
+            true
+        }
+    };
+
+    if stop_byte || !acknowledged {
+        let mut buffer = vec![ReportId::I2cWrite.get_value(), byte_count as u8, 0x00];
+
+        if !acknowledged {
+            buffer[1].set_bit(Bit7, true);
+            buffer[2] = 3; // NACK received
+        }
+
+        push_report(simulation_data, Pipe::I2CMode, buffer);
+    }
+
+    Ok(())
+}
+
+fn handle_i2c_read(
+    simulation_data: &mut SimulationData,
+    report: &Report,
+) -> Result<(), TransportError> {
+    let byte_count = report.buffer[1] as usize;
+    let address = report.buffer[2] >> 1;
+
+    if 2 + byte_count > simulation_data.special_report_size {
+        return Err(malformed_report(report));
+    }
+
+    let mut buffer = vec![ReportId::I2cRead.get_value(), byte_count as u8];
+
+    match simulation_data.i2c_devices.get_mut(&address) {
+        None => buffer[1].set_bit(Bit7, true),
+        Some(i2c_device) => {
+            for _ in 0..byte_count {
+                buffer.push(i2c_device.registers[i2c_device.register_pointer as usize]);
+
+                i2c_device.register_pointer = i2c_device.register_pointer.wrapping_add(1);
+            }
+        }
+    }
+
+    push_report(simulation_data, Pipe::I2CMode, buffer);
+
+    Ok(())
+}
+
+fn write_i2c_device(i2c_device: &mut SimulatedI2CDevice, data: &[u8], start_byte: bool) {
+    let mut iterator = data.iter();
+
+    if start_byte {
+        match iterator.next() {
+            None => return,
+            Some(register) => i2c_device.register_pointer = *register,
+        }
+    }
+
+    for value in iterator {
+        i2c_device.registers[i2c_device.register_pointer as usize] = *value;
+        i2c_device.register_pointer = i2c_device.register_pointer.wrapping_add(1);
+    }
+}
+
+fn handle_spi_transfer(
+    simulation_data: &mut SimulationData,
+    report: &Report,
+) -> Result<(), TransportError> {
+    let (data_start, byte_count) = match simulation_data.device_type {
+        IOWarriorType::IOWarrior24 | IOWarriorType::IOWarrior24PowerVampire => {
+            (2, (report.buffer[1] & 0x3F) as usize)
+        }
+        IOWarriorType::IOWarrior56 | IOWarriorType::IOWarrior56Dongle => {
+            (3, report.buffer[1] as usize)
+        }
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior28Dongle
+        | IOWarriorType::IOWarrior28L
+        | IOWarriorType::IOWarrior100 => return Ok(()),
+    };
+
+    let data = match report.buffer.get(data_start..(data_start + byte_count)) {
+        None => return Err(malformed_report(report)),
+        Some(x) => x,
+    };
+
+    let mut buffer = vec![ReportId::SpiTransfer.get_value(), data.len() as u8];
+
+    buffer.extend(data); // MISO is looped back to MOSI.
+
+    push_report(simulation_data, Pipe::SpecialMode, buffer);
+
+    Ok(())
+}
+
+fn malformed_report(report: &Report) -> TransportError {
+    TransportError::Other(format!(
+        "Simulated firmware rejected malformed report: {:02X?}",
+        report.buffer
+    ))
+}
+
+fn create_adc_report(simulation_data: &mut SimulationData) -> Vec<u8> {
+    let channel_count = simulation_data.adc_channel_count.clamp(1, 8) as usize;
+
+    let samples_count = match simulation_data.device_type {
+        IOWarriorType::IOWarrior56 | IOWarriorType::IOWarrior56Dongle => match channel_count {
+            1 | 2 => 8,
+            _ => channel_count,
+        },
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior28Dongle
+        | IOWarriorType::IOWarrior28L
+        | IOWarriorType::IOWarrior100 => (30 / channel_count) * channel_count,
+    };
+
+    let mut buffer = vec![
+        ReportId::AdcRead.get_value(),
+        simulation_data.adc_packet_number,
+    ];
+
+    for index in 0..samples_count {
+        buffer.extend(simulation_data.adc_values[index % channel_count].to_le_bytes());
+    }
+
+    buffer.resize(simulation_data.special_report_size, 0u8);
+
+    simulation_data.adc_packet_number = simulation_data.adc_packet_number.wrapping_add(1);
+
+    buffer
+}
+
+#[inline]
+fn read_u16(bytes: &[u8], index: usize) -> u16 {
+    u16::from_le_bytes([bytes[index], bytes[index + 1]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_special_report(simulation_data: &SimulationData, bytes: &[u8]) -> Report {
+        let mut buffer = vec![0u8; simulation_data.special_report_size];
+
+        buffer[..bytes.len()].copy_from_slice(bytes);
+
+        Report {
+            buffer,
+            pipe: Pipe::SpecialMode,
+        }
+    }
+
+    #[test]
+    fn malformed_i2c_write_is_rejected() {
+        let mut simulation_data = new_simulation_data(IOWarriorType::IOWarrior24);
+
+        simulation_data.i2c_enabled = true;
+
+        let report = new_special_report(&simulation_data, &[0x02, 0xFF, 0x40]);
+
+        assert!(handle_report(&mut simulation_data, &report).is_err());
+    }
+
+    #[test]
+    fn malformed_i2c_read_is_rejected() {
+        let mut simulation_data = new_simulation_data(IOWarriorType::IOWarrior24);
+
+        simulation_data.i2c_enabled = true;
+
+        let report = new_special_report(&simulation_data, &[0x03, 0x3F, 0x41]);
+
+        assert!(handle_report(&mut simulation_data, &report).is_err());
+    }
+
+    #[test]
+    fn malformed_spi_transfer_is_rejected() {
+        for device_type in [IOWarriorType::IOWarrior24, IOWarriorType::IOWarrior56] {
+            let mut simulation_data = new_simulation_data(device_type);
+
+            simulation_data.spi_enabled = true;
+
+            let report = new_special_report(&simulation_data, &[0x09, 0x3F]);
+
+            assert!(handle_report(&mut simulation_data, &report).is_err());
+        }
+    }
+
+    #[test]
+    fn i2c_write_then_read_returns_registers() {
+        let mut simulation_data = new_simulation_data(IOWarriorType::IOWarrior24);
+
+        simulation_data.i2c_enabled = true;
+        simulation_data
+            .i2c_devices
+            .insert(0x20, SimulatedI2CDevice::default());
+
+        let write_report =
+            new_special_report(&simulation_data, &[0x02, 0xC4, 0x40, 0x10, 0xAA, 0xBB]);
+        let pointer_report = new_special_report(&simulation_data, &[0x02, 0xC2, 0x40, 0x10]);
+        let read_report = new_special_report(&simulation_data, &[0x03, 0x02, 0x41]);
+
+        handle_report(&mut simulation_data, &write_report).unwrap();
+        handle_report(&mut simulation_data, &pointer_report).unwrap();
+        handle_report(&mut simulation_data, &read_report).unwrap();
+
+        assert_eq!(
+            next_report(&mut simulation_data, Pipe::I2CMode).unwrap()[2],
+            0
+        );
+        assert_eq!(
+            next_report(&mut simulation_data, Pipe::I2CMode).unwrap()[2],
+            0
+        );
+        assert_eq!(
+            next_report(&mut simulation_data, Pipe::I2CMode).unwrap()[..4],
+            [0x03, 0x02, 0xAA, 0xBB]
+        );
+    }
+
+    #[test]
+    fn i2c_write_to_missing_device_is_not_acknowledged() {
+        let mut simulation_data = new_simulation_data(IOWarriorType::IOWarrior24);
+
+        simulation_data.i2c_enabled = true;
+
+        let report = new_special_report(&simulation_data, &[0x02, 0xC2, 0x40, 0x10]);
+
+        handle_report(&mut simulation_data, &report).unwrap();
+
+        let buffer = next_report(&mut simulation_data, Pipe::I2CMode).unwrap();
+
+        assert!(buffer[1].get_bit(Bit7));
+    }
+
+    #[test]
+    fn spi_transfer_loops_back() {
+        let mut simulation_data = new_simulation_data(IOWarriorType::IOWarrior24);
+
+        simulation_data.spi_enabled = true;
+
+        let report = new_special_report(&simulation_data, &[0x09, 0x03, 0x01, 0x02, 0x03]);
+
+        handle_report(&mut simulation_data, &report).unwrap();
+
+        assert_eq!(
+            next_report(&mut simulation_data, Pipe::SpecialMode).unwrap()[..5],
+            [0x09, 0x03, 0x01, 0x02, 0x03]
+        );
+    }
+
+    #[test]
+    fn pin_changes_create_reports() {
+        let mut simulation_data = new_simulation_data(IOWarriorType::IOWarrior24);
+
+        update_pins(&mut simulation_data, |x| x.pins_external[0] = 0xFE);
+        update_pins(&mut simulation_data, |x| x.pins_external[0] = 0xFE);
+
+        assert_eq!(
+            next_report(&mut simulation_data, Pipe::IOPins).unwrap(),
+            [0x00, 0xFE, 0xFF]
+        );
+        assert_eq!(next_report(&mut simulation_data, Pipe::IOPins), None);
+    }
+
+    #[test]
+    fn adc_report_repeats_channel_values() {
+        let mut simulation_data = new_simulation_data(IOWarriorType::IOWarrior28);
+
+        simulation_data.adc_enabled = true;
+        simulation_data.adc_channel_count = 2;
+        simulation_data.adc_values[0] = 0x1234;
+        simulation_data.adc_values[1] = 0x0567;
+
+        let buffer = next_report(&mut simulation_data, Pipe::ADCMode).unwrap();
+
+        assert_eq!(
+            buffer[..8],
+            [0x1D, 0x00, 0x34, 0x12, 0x67, 0x05, 0x34, 0x12]
+        );
+        assert_eq!(
+            next_report(&mut simulation_data, Pipe::ADCMode).unwrap()[1],
+            1
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use crate::communication::sim::{firmware_service, CommunicationData, Simulation};
+use crate::communication::{
+    CancelFlag, CommunicationBackend, DevicePath, IOWarriorDescriptor, InitializationConfig,
+    InitializationError, USBInfo, USBSpeed,
+};
+use crate::iowarrior::{iowarrior_service, IOWarrior, IOWarriorType};
+use std::sync::{Arc, Condvar, Mutex};
+
+const SIMULATED_DEVICE_TYPES: [IOWarriorType; 9] = [
+    IOWarriorType::IOWarrior40,
+    IOWarriorType::IOWarrior24,
+    IOWarriorType::IOWarrior24PowerVampire,
+    IOWarriorType::IOWarrior56,
+    IOWarriorType::IOWarrior56Dongle,
+    IOWarriorType::IOWarrior28,
+    IOWarriorType::IOWarrior28Dongle,
+    IOWarriorType::IOWarrior28L,
+    IOWarriorType::IOWarrior100,
+];
+
+pub fn list_iowarriors() -> Result<Vec<IOWarriorDescriptor>, InitializationError> {
+    Ok(SIMULATED_DEVICE_TYPES
+        .iter()
+        .enumerate()
+        .map(|(index, device_type)| IOWarriorDescriptor {
+            serial_number: get_simulated_serial_number(index),
+            device_type: *device_type,
+            device_revision: get_simulated_revision(*device_type),
+            backend: CommunicationBackend::Simulation,
+            device_path: DevicePath::Index(index as u32),
+            interface_count: get_simulated_interface_count(*device_type),
+        })
+        .collect())
+}
+
+pub fn get_iowarriors(
+    config: &InitializationConfig,
+) -> Result<Vec<IOWarrior>, InitializationError> {
+    let mut vec: Vec<IOWarrior> = Vec::new();
+
+    for (index, device_type) in SIMULATED_DEVICE_TYPES.iter().enumerate() {
+        let (iowarrior, _) = get_simulated_iowarrior_with_config(
+            *device_type,
+            get_simulated_revision(*device_type),
+            &get_simulated_serial_number(index),
+            config,
+        )?;
+
+        vec.push(iowarrior);
+    }
+
+    Ok(vec)
+}
+
+pub fn get_iowarrior(
+    serial_number: &str,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    match (0..SIMULATED_DEVICE_TYPES.len())
+        .find(|index| get_simulated_serial_number(*index) == serial_number)
+    {
+        None => Err(InitializationError::NotFound(String::from(serial_number))),
+        Some(index) => {
+            let device_type = SIMULATED_DEVICE_TYPES[index];
+
+            let (iowarrior, _) = get_simulated_iowarrior_with_config(
+                device_type,
+                get_simulated_revision(device_type),
+                serial_number,
+                config,
+            )?;
+
+            Ok(iowarrior)
+        }
+    }
+}
+
+pub fn get_iowarrior_by_path(
+    device_path: &DevicePath,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    match device_path {
+        DevicePath::Index(index) if (*index as usize) < SIMULATED_DEVICE_TYPES.len() => {
+            let index = *index as usize;
+            let device_type = SIMULATED_DEVICE_TYPES[index];
+
+            let (iowarrior, _) = get_simulated_iowarrior_with_config(
+                device_type,
+                get_simulated_revision(device_type),
+                &get_simulated_serial_number(index),
+                config,
+            )?;
+
+            Ok(iowarrior)
+        }
+        _ => Err(InitializationError::NotFound(device_path.to_string())),
+    }
+}
+
+pub fn get_simulated_iowarrior(
+    device_type: IOWarriorType,
+    device_revision: u16,
+    device_serial: &str,
+) -> Result<(IOWarrior, Simulation), InitializationError> {
+    get_simulated_iowarrior_with_config(
+        device_type,
+        device_revision,
+        device_serial,
+        &InitializationConfig::default(),
+    )
+}
+
+pub fn get_simulated_iowarrior_with_config(
+    device_type: IOWarriorType,
+    device_revision: u16,
+    device_serial: &str,
+    config: &InitializationConfig,
+) -> Result<(IOWarrior, Simulation), InitializationError> {
+    let simulation = Simulation {
+        simulation_data: Arc::new(Mutex::new(firmware_service::new_simulation_data(
+            device_type,
+        ))),
+        report_condvar: Arc::new(Condvar::new()),
+    };
+
+    let communication_data = CommunicationData {
+        simulation_data: simulation.simulation_data.clone(),
+        report_condvar: simulation.report_condvar.clone(),
+        read_timeout: None,
+        cancel_flag: Arc::new(CancelFlag::default()),
+        usb_info: get_simulated_usb_info(device_type),
+    };
+
+    let product_type = match device_type {
+        IOWarriorType::IOWarrior56Dongle => IOWarriorType::IOWarrior56,
+        IOWarriorType::IOWarrior28Dongle => IOWarriorType::IOWarrior28,
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior56
+        | IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior28L
+        | IOWarriorType::IOWarrior100 => device_type,
+    };
+
+    let iowarrior = iowarrior_service::create_iowarrior(
+        product_type,
+        device_revision,
+        String::from(device_serial),
+        Box::new(communication_data),
+        config,
+    )?;
+
+    Ok((iowarrior, simulation))
+}
+
+fn get_simulated_serial_number(index: usize) -> String {
+    format!("{:08X}", index + 1)
+}
+
+fn get_simulated_revision(device_type: IOWarriorType) -> u16 {
+    match device_type {
+        IOWarriorType::IOWarrior40 => 0x1039,
+        IOWarriorType::IOWarrior56 | IOWarriorType::IOWarrior56Dongle => 0x2013,
+        IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior28Dongle
+        | IOWarriorType::IOWarrior28L
+        | IOWarriorType::IOWarrior100 => 0x1011,
+    }
+}
+
+fn get_simulated_usb_info(device_type: IOWarriorType) -> USBInfo {
+    let standard_packet_size =
+        (iowarrior_service::get_standard_report_size(device_type) - 1) as u16;
+    let special_packet_size = iowarrior_service::get_special_report_size(device_type) as u16;
+
+    let mut packet_sizes =
+        vec![special_packet_size; get_simulated_interface_count(device_type) as usize];
+    packet_sizes[0] = standard_packet_size;
+
+    USBInfo {
+        speed: Some(USBSpeed::Full),
+        max_power: None,
+        packet_sizes,
+    }
+}
+
+fn get_simulated_interface_count(device_type: IOWarriorType) -> u8 {
+    match device_type {
+        IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior28Dongle
+        | IOWarriorType::IOWarrior100 => 4,
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior28L
+        | IOWarriorType::IOWarrior56
+        | IOWarriorType::IOWarrior56Dongle => 2,
+    }
+}
+
+// Shared fixture for the unit tests, a simulated device of the given type.
+#[cfg(test)]
+pub fn get_test_iowarrior(device_type: IOWarriorType) -> (IOWarrior, Simulation) {
+    get_simulated_iowarrior(device_type, get_simulated_revision(device_type), "00000001").unwrap()
+}
@@ -0,0 +1,12 @@
+mod communication_data;
+mod communication_service;
+mod firmware_service;
+pub(crate) mod initialization_service;
+mod simulation;
+mod simulation_data;
+mod simulation_error;
+
+pub(crate) use self::communication_data::*;
+pub use self::simulation::*;
+pub(crate) use self::simulation_data::*;
+pub use self::simulation_error::*;
@@ -0,0 +1,194 @@
+use crate::adc::ADCChannel;
+use crate::bits::{Bit, Bitmasking};
+use crate::communication::sim::{
+    firmware_service, SimulatedI2CDevice, SimulationData, SimulationError,
+};
+use crate::digital::digital_service;
+use crate::iowarrior::Peripheral;
+use crate::pwm::PWMChannel;
+use embedded_hal::digital::PinState;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub(crate) simulation_data: Arc<Mutex<SimulationData>>,
+    pub(crate) report_condvar: Arc<Condvar>,
+}
+
+impl fmt::Display for Simulation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Simulation {
+    pub fn set_input_pin(&self, pin: u8, pin_state: PinState) -> Result<(), SimulationError> {
+        let mut simulation_data = self.simulation_data.lock().unwrap();
+
+        check_pin(&simulation_data, pin)?;
+
+        let byte_index = (pin as usize) / 8usize;
+        let bit_index = Bit::from_u8(pin % 8u8);
+
+        firmware_service::update_pins(&mut simulation_data, |x| {
+            x.pins_external[byte_index].set_bit(bit_index, bool::from(pin_state))
+        });
+
+        self.report_condvar.notify_all();
+
+        Ok(())
+    }
+
+    pub fn get_output_pin(&self, pin: u8) -> Result<PinState, SimulationError> {
+        let simulation_data = self.simulation_data.lock().unwrap();
+
+        check_pin(&simulation_data, pin)?;
+
+        let byte_index = (pin as usize) / 8usize;
+        let bit_index = Bit::from_u8(pin % 8u8);
+
+        Ok(PinState::from(
+            simulation_data.pins_output[byte_index].get_bit(bit_index),
+        ))
+    }
+
+    pub fn add_i2c_device(&self, address: u8) {
+        let mut simulation_data = self.simulation_data.lock().unwrap();
+
+        simulation_data
+            .i2c_devices
+            .insert(address, SimulatedI2CDevice::default());
+    }
+
+    pub fn set_i2c_register(&self, address: u8, register: u8, value: u8) {
+        let mut simulation_data = self.simulation_data.lock().unwrap();
+
+        let i2c_device = simulation_data.i2c_devices.entry(address).or_default();
+
+        i2c_device.registers[register as usize] = value;
+    }
+
+    pub fn get_i2c_register(&self, address: u8, register: u8) -> Option<u8> {
+        let simulation_data = self.simulation_data.lock().unwrap();
+
+        simulation_data
+            .i2c_devices
+            .get(&address)
+            .map(|x| x.registers[register as usize])
+    }
+
+    pub fn set_adc_value(&self, channel: ADCChannel, value: u16) {
+        let mut simulation_data = self.simulation_data.lock().unwrap();
+
+        simulation_data.adc_values[(channel.get_value() - 1) as usize] = value;
+    }
+
+    pub fn get_pwm_duty_cycle(&self, channel: PWMChannel) -> Option<u16> {
+        let simulation_data = self.simulation_data.lock().unwrap();
+
+        firmware_service::get_pwm_duty_cycle(&simulation_data, channel.get_value())
+    }
+
+    pub fn is_peripheral_enabled(&self, peripheral: Peripheral) -> bool {
+        let simulation_data = self.simulation_data.lock().unwrap();
+
+        match peripheral {
+            Peripheral::I2C => simulation_data.i2c_enabled,
+            Peripheral::PWM => simulation_data.pwm_enabled,
+            Peripheral::SPI => simulation_data.spi_enabled,
+            Peripheral::ADC => simulation_data.adc_enabled,
+        }
+    }
+}
+
+fn check_pin(simulation_data: &SimulationData, pin: u8) -> Result<(), SimulationError> {
+    match digital_service::get_is_valid_gpio(simulation_data.device_type, pin) {
+        true => Ok(()),
+        false => Err(SimulationError::PinNotExisting),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::sim::initialization_service::get_test_iowarrior;
+    use crate::iowarrior::IOWarriorType;
+    use embedded_hal::digital::{InputPin, OutputPin};
+    use embedded_hal::i2c::I2c;
+    use embedded_hal::spi::SpiBus;
+
+    #[test]
+    fn gpio_pins_are_simulated() {
+        let (iowarrior, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        let mut output_pin = iowarrior.setup_output_as_high(4).unwrap();
+        let mut input_pin = iowarrior.setup_input(5).unwrap();
+
+        output_pin.set_low().unwrap();
+        simulation.set_input_pin(5, PinState::Low).unwrap();
+
+        assert_eq!(simulation.get_output_pin(4).unwrap(), PinState::Low);
+        assert!(input_pin.is_low().unwrap());
+    }
+
+    #[test]
+    fn invalid_pins_are_rejected() {
+        let (_, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        assert!(matches!(
+            simulation.set_input_pin(16, PinState::Low),
+            Err(SimulationError::PinNotExisting)
+        ));
+        assert!(matches!(
+            simulation.get_output_pin(255),
+            Err(SimulationError::PinNotExisting)
+        ));
+    }
+
+    #[test]
+    fn i2c_devices_are_simulated() {
+        let (iowarrior, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior56);
+
+        simulation.add_i2c_device(0x40);
+        simulation.set_i2c_register(0x40, 0x20, 0x55);
+
+        let mut i2c = iowarrior.setup_i2c().unwrap();
+        let mut buffer = [0u8; 1];
+
+        i2c.write(0x40, &[0x10, 0x01, 0x02]).unwrap();
+        i2c.write_read(0x40, &[0x20], &mut buffer).unwrap();
+
+        assert_eq!(simulation.get_i2c_register(0x40, 0x11), Some(0x02));
+        assert_eq!(buffer, [0x55]);
+        assert!(i2c.write(0x41, &[0x00]).is_err());
+    }
+
+    #[test]
+    fn spi_is_looped_back() {
+        let (iowarrior, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        let mut spi = iowarrior.setup_spi().unwrap();
+        let mut buffer = [0u8; 3];
+
+        spi.transfer(&mut buffer, &[0x01, 0x02, 0x03]).unwrap();
+
+        assert!(simulation.is_peripheral_enabled(Peripheral::SPI));
+        assert_eq!(buffer, [0x01, 0x02, 0x03]);
+    }
+
+    #[test]
+    fn adc_values_are_simulated() {
+        let (iowarrior, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior56);
+
+        simulation.set_adc_value(ADCChannel::First, 1234);
+
+        let mut adc = iowarrior.setup_adc().unwrap();
+        let mut samples = [None; 8];
+
+        adc.read(&mut samples).unwrap();
+
+        assert_eq!(samples[0].unwrap().channel, ADCChannel::First);
+        assert_eq!(samples[0].unwrap().value, 1234);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::iowarrior::{IOWarriorType, Pipe};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+#[derive(Debug)]
+pub struct SimulationData {
+    pub device_type: IOWarriorType,
+    pub standard_report_size: usize,
+    pub special_report_size: usize,
+    pub pins_output: Vec<u8>,
+    pub pins_external: Vec<u8>,
+    pub pending_reports: HashMap<Pipe, VecDeque<Vec<u8>>>,
+    pub i2c_enabled: bool,
+    pub i2c_devices: HashMap<u8, SimulatedI2CDevice>,
+    pub spi_enabled: bool,
+    pub adc_enabled: bool,
+    pub adc_channel_count: u8,
+    pub adc_packet_number: u8,
+    pub adc_values: [u16; 8],
+    pub pwm_enabled: bool,
+    pub pwm_setup_report: Option<Vec<u8>>,
+    pub pwm_parameters_report: Option<Vec<u8>>,
+}
+
+impl fmt::Display for SimulationData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SimulatedI2CDevice {
+    pub registers: Vec<u8>,
+    pub register_pointer: u8,
+}
+
+impl Default for SimulatedI2CDevice {
+    fn default() -> Self {
+        SimulatedI2CDevice {
+            registers: vec![0u8; 256],
+            register_pointer: 0,
+        }
+    }
+}
+
+impl fmt::Display for SimulatedI2CDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,8 @@
+use thiserror::Error;
+
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum SimulationError {
+    #[error("Pin not existing.")]
+    PinNotExisting,
+}
@@ -0,0 +1,4 @@
+mod trace_entry;
+pub(crate) mod trace_service;
+
+pub(crate) use self::trace_entry::*;
@@ -0,0 +1,59 @@
+use crate::iowarrior::{IOWarriorType, Pipe};
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TraceHeader {
+    pub device_type: IOWarriorType,
+    pub device_revision: u16,
+    pub device_serial: String,
+}
+
+impl fmt::Display for TraceHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TraceDirection {
+    Write,
+    Read,
+    ReadNonBlocking,
+}
+
+impl fmt::Display for TraceDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TraceError {
+    IncompleteSend { sent: usize, all: usize },
+    IncompleteRead { read: usize, expected: usize },
+    IO(io::ErrorKind),
+    Other(String),
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceEntry {
+    pub timestamp: Duration,
+    pub direction: TraceDirection,
+    pub pipe: Pipe,
+    pub buffer: Option<Vec<u8>>,
+    pub error: Option<TraceError>,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,324 @@
+use crate::communication::trace::{TraceDirection, TraceEntry, TraceError, TraceHeader};
+use crate::communication::TransportError;
+use crate::iowarrior::{IOWarriorType, Pipe};
+use std::io;
+use std::time::Duration;
+
+pub const TRACE_SIGNATURE: &str = "# IOWarrior trace";
+
+const DEVICE_TYPES: [IOWarriorType; 9] = [
+    IOWarriorType::IOWarrior40,
+    IOWarriorType::IOWarrior24,
+    IOWarriorType::IOWarrior24PowerVampire,
+    IOWarriorType::IOWarrior56,
+    IOWarriorType::IOWarrior56Dongle,
+    IOWarriorType::IOWarrior28,
+    IOWarriorType::IOWarrior28Dongle,
+    IOWarriorType::IOWarrior28L,
+    IOWarriorType::IOWarrior100,
+];
+
+const PIPES: [Pipe; 4] = [
+    Pipe::IOPins,
+    Pipe::SpecialMode,
+    Pipe::I2CMode,
+    Pipe::ADCMode,
+];
+
+const ERROR_KINDS: [io::ErrorKind; 17] = [
+    io::ErrorKind::NotFound,
+    io::ErrorKind::PermissionDenied,
+    io::ErrorKind::ConnectionReset,
+    io::ErrorKind::NotConnected,
+    io::ErrorKind::BrokenPipe,
+    io::ErrorKind::AlreadyExists,
+    io::ErrorKind::WouldBlock,
+    io::ErrorKind::InvalidInput,
+    io::ErrorKind::InvalidData,
+    io::ErrorKind::TimedOut,
+    io::ErrorKind::WriteZero,
+    io::ErrorKind::Interrupted,
+    io::ErrorKind::Unsupported,
+    io::ErrorKind::UnexpectedEof,
+    io::ErrorKind::OutOfMemory,
+    io::ErrorKind::ResourceBusy,
+    io::ErrorKind::Other,
+];
+
+pub fn format_header(header: &TraceHeader) -> String {
+    format!(
+        "device {:?} {:04X} {}",
+        header.device_type, header.device_revision, header.device_serial
+    )
+}
+
+pub fn parse_header(line: &str) -> Option<TraceHeader> {
+    let mut tokens = line.splitn(4, ' ');
+
+    if tokens.next()? != "device" {
+        return None;
+    }
+
+    let device_type = tokens.next()?;
+    let device_type = *DEVICE_TYPES
+        .iter()
+        .find(|x| format!("{:?}", x) == device_type)?;
+
+    let device_revision = u16::from_str_radix(tokens.next()?, 16).ok()?;
+    let device_serial = String::from(tokens.next()?);
+
+    Some(TraceHeader {
+        device_type,
+        device_revision,
+        device_serial,
+    })
+}
+
+pub fn format_entry(entry: &TraceEntry) -> String {
+    let direction = match entry.direction {
+        TraceDirection::Write => "W",
+        TraceDirection::Read => "R",
+        TraceDirection::ReadNonBlocking => "N",
+    };
+
+    let buffer = match &entry.buffer {
+        None => String::from("-"),
+        Some(buffer) => buffer.iter().map(|x| format!("{:02x}", x)).collect(),
+    };
+
+    let mut line = format!(
+        "{}.{:06} {} {:?} {}",
+        entry.timestamp.as_secs(),
+        entry.timestamp.subsec_micros(),
+        direction,
+        entry.pipe,
+        buffer
+    );
+
+    match &entry.error {
+        None => {}
+        Some(TraceError::IncompleteSend { sent, all }) => {
+            line.push_str(&format!(" !IncompleteSend {} {}", sent, all))
+        }
+        Some(TraceError::IncompleteRead { read, expected }) => {
+            line.push_str(&format!(" !IncompleteRead {} {}", read, expected))
+        }
+        Some(TraceError::IO(kind)) => line.push_str(&format!(" !IO {:?}", kind)),
+        Some(TraceError::Other(message)) => {
+            line.push_str(&format!(" !Other {}", message.replace('\n', " ")))
+        }
+    }
+
+    line
+}
+
+pub fn parse_entry(line: &str) -> Option<TraceEntry> {
+    let mut tokens = line.splitn(5, ' ');
+
+    let timestamp = {
+        let (secs, micros) = tokens.next()?.split_once('.')?;
+
+        Duration::from_secs(secs.parse().ok()?) + Duration::from_micros(micros.parse().ok()?)
+    };
+
+    let direction = match tokens.next()? {
+        "W" => TraceDirection::Write,
+        "R" => TraceDirection::Read,
+        "N" => TraceDirection::ReadNonBlocking,
+        _ => return None,
+    };
+
+    let pipe = tokens.next()?;
+    let pipe = *PIPES.iter().find(|x| format!("{:?}", x) == pipe)?;
+
+    let buffer = match tokens.next()? {
+        "-" => None,
+        hex => Some(parse_hex(hex)?),
+    };
+
+    let error = match tokens.next() {
+        None => None,
+        Some(error) => Some(parse_error(error)?),
+    };
+
+    Some(TraceEntry {
+        timestamp,
+        direction,
+        pipe,
+        buffer,
+        error,
+    })
+}
+
+pub fn to_trace_error(error: &TransportError) -> TraceError {
+    match error {
+        TransportError::ShortWrite { written, expected } => TraceError::IncompleteSend {
+            sent: *written,
+            all: *expected,
+        },
+        TransportError::ShortRead { read, expected } => TraceError::IncompleteRead {
+            read: *read,
+            expected: *expected,
+        },
+        TransportError::IO(error) => TraceError::IO(error.kind()),
+        TransportError::Timeout => TraceError::IO(io::ErrorKind::TimedOut),
+        TransportError::Disconnected => TraceError::IO(io::ErrorKind::NotConnected),
+        TransportError::Cancelled => TraceError::IO(io::ErrorKind::Interrupted),
+        _ => TraceError::Other(error.to_string()),
+    }
+}
+
+pub fn to_transport_error(error: &TraceError) -> TransportError {
+    match error {
+        TraceError::IncompleteSend { sent, all } => TransportError::ShortWrite {
+            written: *sent,
+            expected: *all,
+        },
+        TraceError::IncompleteRead { read, expected } => TransportError::ShortRead {
+            read: *read,
+            expected: *expected,
+        },
+        TraceError::IO(io::ErrorKind::Interrupted) => TransportError::Cancelled,
+        TraceError::IO(kind) => TransportError::from(io::Error::from(*kind)),
+        TraceError::Other(message) => TransportError::Other(message.clone()),
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+fn parse_error(error: &str) -> Option<TraceError> {
+    let (name, arguments) = error.strip_prefix('!')?.split_once(' ')?;
+
+    match name {
+        "IncompleteSend" => {
+            let (sent, all) = arguments.split_once(' ')?;
+
+            Some(TraceError::IncompleteSend {
+                sent: sent.parse().ok()?,
+                all: all.parse().ok()?,
+            })
+        }
+        "IncompleteRead" => {
+            let (read, expected) = arguments.split_once(' ')?;
+
+            Some(TraceError::IncompleteRead {
+                read: read.parse().ok()?,
+                expected: expected.parse().ok()?,
+            })
+        }
+        "IO" => Some(TraceError::IO(
+            *ERROR_KINDS
+                .iter()
+                .find(|x| format!("{:?}", x) == arguments)
+                .unwrap_or(&io::ErrorKind::Other),
+        )),
+        "Other" => Some(TraceError::Other(String::from(arguments))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_entry(buffer: Option<Vec<u8>>, error: Option<TraceError>) -> TraceEntry {
+        TraceEntry {
+            timestamp: Duration::from_micros(1_000_042),
+            direction: TraceDirection::Read,
+            pipe: Pipe::I2CMode,
+            buffer,
+            error,
+        }
+    }
+
+    #[test]
+    fn header_round_trip() {
+        for device_serial in ["0000ABCD", "", "with space"] {
+            let header = TraceHeader {
+                device_type: IOWarriorType::IOWarrior28Dongle,
+                device_revision: 0x1021,
+                device_serial: String::from(device_serial),
+            };
+
+            assert_eq!(parse_header(&format_header(&header)), Some(header));
+        }
+    }
+
+    #[test]
+    fn entry_round_trip() {
+        let entries = [
+            get_entry(Some(vec![0x02, 0xC2, 0xFF]), None),
+            get_entry(None, Some(TraceError::IO(io::ErrorKind::TimedOut))),
+            get_entry(
+                Some(vec![0x00]),
+                Some(TraceError::IncompleteRead {
+                    read: 1,
+                    expected: 8,
+                }),
+            ),
+            get_entry(None, Some(TraceError::IncompleteSend { sent: 3, all: 8 })),
+            get_entry(None, Some(TraceError::Other(String::from("some error")))),
+        ];
+
+        for entry in entries {
+            let line = format_entry(&entry);
+
+            assert_eq!(parse_entry(&line), Some(entry), "{}", line);
+        }
+    }
+
+    #[test]
+    fn entry_format() {
+        assert_eq!(
+            format_entry(&get_entry(Some(vec![0x02, 0xC2]), None)),
+            "1.000042 R I2CMode 02c2"
+        );
+    }
+
+    #[test]
+    fn invalid_lines_are_rejected() {
+        for line in [
+            "",
+            "1.0 X I2CMode -",
+            "1.0 R NoPipe -",
+            "1.0 R I2CMode 0",
+            "1.0 R I2CMode zz",
+            "1.0 R I2CMode - !Unknown 1",
+            "1 R I2CMode -",
+        ] {
+            assert_eq!(parse_entry(line), None, "{}", line);
+        }
+
+        assert_eq!(parse_header("device IOWarrior99 1000 serial"), None);
+        assert_eq!(parse_header("devices IOWarrior40 1000 serial"), None);
+    }
+
+    #[test]
+    fn errors_map_back_to_transport_errors() {
+        for error in [
+            TransportError::Timeout,
+            TransportError::Disconnected,
+            TransportError::Cancelled,
+            TransportError::ShortRead {
+                read: 1,
+                expected: 8,
+            },
+        ] {
+            let trace_error = to_trace_error(&error);
+
+            assert_eq!(
+                to_transport_error(&trace_error).to_string(),
+                error.to_string()
+            );
+        }
+    }
+}
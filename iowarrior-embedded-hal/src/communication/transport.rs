@@ -0,0 +1,50 @@
+use crate::communication::{TransportError, USBInfo};
+use crate::iowarrior::Report;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub(crate) const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub trait Transport: fmt::Debug + Send {
+    fn write_report(&mut self, report: &Report) -> Result<(), TransportError>;
+
+    fn read_report_non_blocking(
+        &mut self,
+        report: Report,
+    ) -> Result<Option<Report>, TransportError>;
+
+    fn read_report(&mut self, report: Report) -> Result<Report, TransportError>;
+
+    fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<(), TransportError>;
+
+    fn get_canceller(&self) -> Arc<dyn Canceller>;
+
+    fn get_usb_info(&self) -> USBInfo;
+
+    // Wrapping transports take over the reconnected transport, others hand it back.
+    fn replace_transport(
+        &mut self,
+        communication_data: Box<dyn Transport>,
+    ) -> Option<Box<dyn Transport>> {
+        Some(communication_data)
+    }
+}
+
+pub trait Canceller: fmt::Debug + Send + Sync {
+    fn cancel(&self);
+}
+
+pub(crate) fn next_poll_interval(deadline: Option<Instant>) -> Result<Duration, TransportError> {
+    match deadline {
+        None => Ok(CANCEL_POLL_INTERVAL),
+        Some(deadline) => match deadline.saturating_duration_since(Instant::now()) {
+            Duration::ZERO => Err(TransportError::Timeout),
+            remaining => Ok(Duration::min(remaining, CANCEL_POLL_INTERVAL)),
+        },
+    }
+}
@@ -0,0 +1,78 @@
+use std::io;
+use thiserror::Error;
+
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum TransportError {
+    #[error("IO error.")]
+    IO(#[source] io::Error),
+    #[error("Incomplete write, {written} of {expected} bytes written.")]
+    ShortWrite { written: usize, expected: usize },
+    #[error("Incomplete read, {read} of {expected} bytes read.")]
+    ShortRead { read: usize, expected: usize },
+    #[error("Timeout while waiting for IOWarrior.")]
+    Timeout,
+    #[error("IOWarrior disconnected.")]
+    Disconnected,
+    #[error("IOWarrior operation cancelled.")]
+    Cancelled,
+    #[error("Transport error: {0}")]
+    Other(String),
+}
+
+impl From<io::Error> for TransportError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotConnected => TransportError::Disconnected,
+            io::ErrorKind::TimedOut => TransportError::Timeout,
+            _ => match error.raw_os_error() {
+                Some(x) if DISCONNECTED_OS_ERRORS.contains(&x) => TransportError::Disconnected,
+                _ => TransportError::IO(error),
+            },
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+const DISCONNECTED_OS_ERRORS: [i32; 2] = [
+    31,   // ERROR_GEN_FAILURE
+    1167, // ERROR_DEVICE_NOT_CONNECTED
+];
+
+#[cfg(not(target_os = "windows"))]
+const DISCONNECTED_OS_ERRORS: [i32; 2] = [
+    19,  // ENODEV
+    108, // ESHUTDOWN
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disconnect_errors_map_to_disconnected() {
+        assert!(matches!(
+            TransportError::from(io::Error::from(io::ErrorKind::NotConnected)),
+            TransportError::Disconnected
+        ));
+
+        for os_error in DISCONNECTED_OS_ERRORS {
+            assert!(matches!(
+                TransportError::from(io::Error::from_raw_os_error(os_error)),
+                TransportError::Disconnected
+            ));
+        }
+    }
+
+    #[test]
+    fn other_errors_keep_their_kind() {
+        assert!(matches!(
+            TransportError::from(io::Error::from(io::ErrorKind::TimedOut)),
+            TransportError::Timeout
+        ));
+        assert!(matches!(
+            TransportError::from(io::Error::from(io::ErrorKind::PermissionDenied)),
+            TransportError::IO(x) if x.kind() == io::ErrorKind::PermissionDenied
+        ));
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct USBInfo {
+    pub speed: Option<USBSpeed>,
+    pub max_power: Option<u16>,
+    pub packet_sizes: Vec<u16>,
+}
+
+impl fmt::Display for USBInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum USBSpeed {
+    Low,
+    Full,
+    High,
+    Super,
+    SuperPlus,
+}
+
+impl fmt::Display for USBSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -1,5 +1,8 @@
+use crate::communication::CancelFlag;
 use hidapi::HidDevice;
 use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum USBPipes {
@@ -24,6 +27,8 @@ impl fmt::Display for USBPipes {
 #[derive(Debug)]
 pub struct CommunicationData {
     pub usb_pipes: USBPipes,
+    pub read_timeout: Option<Duration>,
+    pub cancel_flag: Arc<CancelFlag>,
 }
 
 impl fmt::Display for CommunicationData {
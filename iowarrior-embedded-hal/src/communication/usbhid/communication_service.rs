@@ -1,66 +1,127 @@
-use crate::communication::{CommunicationData, USBPipes};
+use crate::communication::usbhid::{CommunicationData, USBPipes};
+use crate::communication::{next_poll_interval, Canceller, Transport, TransportError, USBInfo};
 use crate::iowarrior::{Pipe, Report};
 use hidapi::{HidDevice, HidError};
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-pub fn write_report(
-    communication_data: &mut CommunicationData,
-    report: &Report,
-) -> Result<(), HidError> {
-    let usb_device = pipe_to_hid_device(&communication_data.usb_pipes, report.pipe);
+impl Transport for CommunicationData {
+    fn write_report(&mut self, report: &Report) -> Result<(), TransportError> {
+        let usb_device = pipe_to_hid_device(&self.usb_pipes, report.pipe);
 
-    let bytes_written = usb_device.write(report.buffer.as_slice())?;
+        let bytes_written = usb_device
+            .write(report.buffer.as_slice())
+            .map_err(to_transport_error)?;
 
-    if bytes_written != report.buffer.len() {
-        return Err(HidError::IncompleteSendError {
-            sent: bytes_written,
-            all: report.buffer.len(),
-        });
+        if bytes_written != report.buffer.len() {
+            return Err(TransportError::ShortWrite {
+                written: bytes_written,
+                expected: report.buffer.len(),
+            });
+        }
+
+        Ok(())
     }
 
-    Ok(())
-}
+    fn read_report_non_blocking(
+        &mut self,
+        mut report: Report,
+    ) -> Result<Option<Report>, TransportError> {
+        let usb_device = pipe_to_hid_device(&self.usb_pipes, report.pipe);
 
-pub fn read_report_non_blocking(
-    communication_data: &mut CommunicationData,
-    mut report: Report,
-) -> Result<Option<Report>, HidError> {
-    let usb_device = pipe_to_hid_device(&communication_data.usb_pipes, report.pipe);
+        usb_device
+            .set_blocking_mode(false)
+            .map_err(to_transport_error)?;
 
-    usb_device.set_blocking_mode(false)?;
+        let bytes_read = usb_device
+            .read(report.buffer.as_mut_slice())
+            .map_err(to_transport_error)?;
 
-    let bytes_read = usb_device.read(report.buffer.as_mut_slice())?;
+        if bytes_read > 0 && bytes_read != report.buffer.len() {
+            return Err(TransportError::ShortRead {
+                read: bytes_read,
+                expected: report.buffer.len(),
+            });
+        }
 
-    if bytes_read > 0 && bytes_read != report.buffer.len() {
-        return Err(HidError::IncompleteSendError {
-            sent: bytes_read,
-            all: report.buffer.len(),
-        });
+        Ok(match bytes_read > 0 {
+            true => Some(report),
+            false => None,
+        })
     }
 
-    Ok(match bytes_read > 0 {
-        true => Some(report),
-        false => None,
-    })
-}
+    fn read_report(&mut self, mut report: Report) -> Result<Report, TransportError> {
+        let usb_device = pipe_to_hid_device(&self.usb_pipes, report.pipe);
+
+        usb_device
+            .set_blocking_mode(true)
+            .map_err(to_transport_error)?;
+
+        let deadline = self.read_timeout.map(|x| Instant::now() + x);
 
-pub fn read_report(
-    communication_data: &mut CommunicationData,
-    mut report: Report,
-) -> Result<Report, HidError> {
-    let usb_device = pipe_to_hid_device(&communication_data.usb_pipes, report.pipe);
+        let bytes_read = loop {
+            if self.cancel_flag.take() {
+                return Err(TransportError::Cancelled);
+            }
 
-    usb_device.set_blocking_mode(true)?;
+            let timeout = next_poll_interval(deadline)?.as_millis().max(1) as i32;
 
-    let bytes_read = usb_device.read(report.buffer.as_mut_slice())?;
+            match usb_device
+                .read_timeout(report.buffer.as_mut_slice(), timeout)
+                .map_err(to_transport_error)?
+            {
+                0 => continue,
+                x => break x,
+            }
+        };
 
-    if bytes_read != report.buffer.len() {
-        return Err(HidError::IncompleteSendError {
-            sent: bytes_read,
-            all: report.buffer.len(),
-        });
+        if bytes_read != report.buffer.len() {
+            return Err(TransportError::ShortRead {
+                read: bytes_read,
+                expected: report.buffer.len(),
+            });
+        }
+
+        Ok(report)
     }
 
-    Ok(report)
+    fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<(), TransportError> {
+        // hidapi writes have no timeout.
+        if write_timeout.is_some() {
+            return Err(TransportError::IO(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Write timeouts are not supported by the USBHID backend.",
+            )));
+        }
+
+        self.read_timeout = read_timeout;
+
+        Ok(())
+    }
+
+    fn get_canceller(&self) -> Arc<dyn Canceller> {
+        self.cancel_flag.clone()
+    }
+
+    fn get_usb_info(&self) -> USBInfo {
+        USBInfo::default()
+    }
+}
+
+pub fn to_transport_error(error: HidError) -> TransportError {
+    match error {
+        HidError::IoError { error } => TransportError::from(error),
+        HidError::IncompleteSendError { sent, all } => TransportError::ShortWrite {
+            written: sent,
+            expected: all,
+        },
+        error => TransportError::IO(io::Error::other(error)),
+    }
 }
 
 fn pipe_to_hid_device(usb_pipes: &USBPipes, pipe: Pipe) -> &HidDevice {
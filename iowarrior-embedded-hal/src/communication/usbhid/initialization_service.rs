@@ -1,30 +1,84 @@
-use crate::communication::{CommunicationData, InitializationError, USBPipes};
+use crate::communication::usbhid::communication_service::to_transport_error;
+use crate::communication::usbhid::{CommunicationData, USBPipes};
+use crate::communication::{
+    CancelFlag, CommunicationBackend, DevicePath, IOWarriorDescriptor, InitializationConfig,
+    InitializationError, TransportError,
+};
 use crate::iowarrior::{iowarrior_service, IOWarrior, IOWarriorType};
-use hidapi::HidError::IoError;
 use hidapi::{DeviceInfo, HidApi, HidDevice};
 use itertools::Itertools;
+#[cfg(target_os = "windows")]
 use std::os::windows::io::AsRawHandle;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+#[cfg(target_os = "windows")]
 use windows::Win32::Devices::HumanInterfaceDevice::{HidD_GetAttributes, HIDD_ATTRIBUTES};
+#[cfg(target_os = "windows")]
 use windows::Win32::Foundation::{BOOLEAN, HWND};
 
 const VENDOR_IDENTIFIER: u16 = 1984;
 
-pub fn get_iowarriors() -> Result<Vec<IOWarrior>, InitializationError> {
-    let api = HidApi::new().map_err(|x| InitializationError::ErrorUSB(x))?;
+pub fn list_iowarriors() -> Result<Vec<IOWarriorDescriptor>, InitializationError> {
+    let api = HidApi::new().map_err(|x| InitializationError::ErrorUSB(to_transport_error(x)))?;
 
     let grouped_usb_devices = api
         .device_list()
         .filter(|x| {
             x.vendor_id() == VENDOR_IDENTIFIER
-                && x.serial_number().is_some()
                 && IOWarriorType::from_device_product_id(x.product_id()).is_some()
         })
-        .into_group_map_by(|x| x.serial_number().unwrap());
+        .into_group_map_by(get_usb_device_key);
+
+    let mut vec: Vec<IOWarriorDescriptor> = Vec::new();
+
+    for ((_, serial_number), device_infos) in grouped_usb_devices {
+        let pipe_0 = get_hid_info(&device_infos, 0)?;
+        let pipe_0_path = get_hid_path(&pipe_0)?;
+
+        let device_type = match IOWarriorType::from_device_product_id(pipe_0.product_id()) {
+            None => continue,
+            Some(x) => x,
+        };
+
+        let device_revision = match pipe_0.release_number() {
+            0 => get_revision(&pipe_0)?,
+            x => x,
+        };
+
+        vec.push(IOWarriorDescriptor {
+            serial_number: String::from(serial_number),
+            device_type,
+            device_revision,
+            backend: CommunicationBackend::USBHID,
+            device_path: DevicePath::Path(String::from(pipe_0_path)),
+            interface_count: device_infos.len() as u8,
+        });
+    }
+
+    vec.sort();
+
+    Ok(vec)
+}
+
+pub fn get_iowarriors(
+    config: &InitializationConfig,
+) -> Result<Vec<IOWarrior>, InitializationError> {
+    let api = HidApi::new().map_err(|x| InitializationError::ErrorUSB(to_transport_error(x)))?;
+
+    let grouped_usb_devices = api
+        .device_list()
+        .filter(|x| {
+            x.vendor_id() == VENDOR_IDENTIFIER
+                && IOWarriorType::from_device_product_id(x.product_id()).is_some()
+        })
+        .into_group_map_by(get_usb_device_key);
 
     let mut vec: Vec<IOWarrior> = Vec::new();
 
-    for (serial_number, device_infos) in grouped_usb_devices {
-        let iowarrior = get_iowarrior_internal(&api, &device_infos, serial_number)?;
+    for ((_, serial_number), device_infos) in grouped_usb_devices {
+        let iowarrior = get_iowarrior_internal(&api, &device_infos, serial_number, config)?;
 
         vec.push(iowarrior);
     }
@@ -32,8 +86,11 @@ pub fn get_iowarriors() -> Result<Vec<IOWarrior>, InitializationError> {
     Ok(vec)
 }
 
-pub fn get_iowarrior(serial_number: &str) -> Result<IOWarrior, InitializationError> {
-    let api = HidApi::new().map_err(|x| InitializationError::ErrorUSB(x))?;
+pub fn get_iowarrior(
+    serial_number: &str,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let api = HidApi::new().map_err(|x| InitializationError::ErrorUSB(to_transport_error(x)))?;
 
     let grouped_usb_device: Vec<&DeviceInfo> = api
         .device_list()
@@ -48,41 +105,98 @@ pub fn get_iowarrior(serial_number: &str) -> Result<IOWarrior, InitializationErr
         return Err(InitializationError::NotFound(String::from(serial_number)));
     }
 
-    get_iowarrior_internal(&api, &grouped_usb_device, serial_number)
+    get_iowarrior_internal(&api, &grouped_usb_device, serial_number, config)
+}
+
+pub fn get_iowarrior_by_path(
+    device_path: &DevicePath,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    let api = HidApi::new().map_err(|x| InitializationError::ErrorUSB(to_transport_error(x)))?;
+
+    let device_infos: Vec<&DeviceInfo> = api
+        .device_list()
+        .filter(|x| {
+            x.vendor_id() == VENDOR_IDENTIFIER
+                && IOWarriorType::from_device_product_id(x.product_id()).is_some()
+        })
+        .collect();
+
+    let requested_device_info = match device_infos.iter().find(|x| match device_path {
+        DevicePath::Path(path) => x.path().to_str().ok() == Some(path.as_str()),
+        DevicePath::Location(location) => get_usb_location(x).as_ref() == Some(location),
+        DevicePath::Index(_) => false,
+    }) {
+        None => return Err(InitializationError::NotFound(device_path.to_string())),
+        Some(x) => *x,
+    };
+
+    let usb_device_path = get_usb_device_path(requested_device_info);
+
+    let grouped_usb_device: Vec<&DeviceInfo> = device_infos
+        .iter()
+        .filter(|x| match &usb_device_path {
+            None => x.serial_number() == requested_device_info.serial_number(),
+            Some(usb_device_path) => get_usb_device_path(x).as_ref() == Some(usb_device_path),
+        })
+        .copied()
+        .collect();
+
+    let serial_number = requested_device_info.serial_number().unwrap_or_default();
+
+    get_iowarrior_internal(&api, &grouped_usb_device, serial_number, config)
 }
 
 fn get_iowarrior_internal(
     api: &HidApi,
     device_infos: &Vec<&DeviceInfo>,
     serial_number: &str,
+    config: &InitializationConfig,
 ) -> Result<IOWarrior, InitializationError> {
     let pipe_0 = get_hid_info(&device_infos, 0)?;
-    let pipe_0_path = get_hid_path(&pipe_0)?;
 
     let device_type = match IOWarriorType::from_device_product_id(pipe_0.product_id()) {
         None => return Err(InitializationError::NotFound(String::from(serial_number))),
         Some(x) => x,
     };
 
-    let device_revision = get_revision(pipe_0_path)?;
+    let device_revision = match pipe_0.release_number() {
+        0 => get_revision(&pipe_0)?,
+        x => x,
+    };
 
     let usb_pipes = open_hid_pipes(&api, device_type, &device_infos)?;
 
-    let communication_data = CommunicationData { usb_pipes };
+    let communication_data = CommunicationData {
+        usb_pipes,
+        read_timeout: None,
+        cancel_flag: Arc::new(CancelFlag::default()),
+    };
 
     iowarrior_service::create_iowarrior(
         device_type,
         device_revision,
         String::from(serial_number),
-        communication_data,
+        Box::new(communication_data),
+        config,
+    )
+}
+
+// Legacy IOWarrior40s have no serial number, so interfaces are grouped by USB device first.
+fn get_usb_device_key<'a>(device_info: &&'a DeviceInfo) -> (Option<PathBuf>, &'a str) {
+    (
+        get_usb_device_path(device_info),
+        device_info.serial_number().unwrap_or_default(),
     )
-    .map_err(|x| InitializationError::ErrorUSB(x))
 }
 
 fn get_hid_path(device_info: &DeviceInfo) -> Result<&str, InitializationError> {
-    device_info.path().to_str().map_err(|x| {
-        InitializationError::InternalError("Error converting USB HID path.".to_owned())
-    })
+    device_info
+        .path()
+        .to_str()
+        .map_err(|x| InitializationError::InvalidPath {
+            path: PathBuf::from(device_info.path().to_string_lossy().into_owned()),
+        })
 }
 
 fn get_hid_info(
@@ -95,9 +209,14 @@ fn get_hid_info(
         .next();
 
     match requested_pipe {
-        None => Err(InitializationError::InternalError(
-            "Missing Pipe.".to_owned(),
-        )),
+        None => Err(InitializationError::MissingInterface {
+            path: device_infos
+                .iter()
+                .map(|x| x.path().to_string_lossy().into_owned())
+                .min()
+                .unwrap_or_default(),
+            interface: pipe_number,
+        }),
         Some(x) => Ok((*x).clone()),
     }
 }
@@ -142,12 +261,48 @@ fn open_hid_pipes(
 
 fn open_hid_pipe(api: &HidApi, pipe: DeviceInfo) -> Result<HidDevice, InitializationError> {
     api.open_path(pipe.path())
-        .map_err(|x| InitializationError::ErrorUSB(x))
+        .map_err(|x| match to_transport_error(x) {
+            TransportError::IO(error) => {
+                InitializationError::from_open_error(&pipe.path().to_string_lossy(), error)
+            }
+            error => InitializationError::ErrorUSB(error),
+        })
 }
 
-fn get_revision(device_path: &str) -> Result<u16, InitializationError> {
+fn get_usb_location(device_info: &DeviceInfo) -> Option<String> {
+    get_usb_device_path(device_info)?
+        .file_name()?
+        .to_str()
+        .map(String::from)
+}
+
+#[cfg(target_os = "windows")]
+fn get_usb_device_path(device_info: &DeviceInfo) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn get_usb_device_path(device_info: &DeviceInfo) -> Option<PathBuf> {
+    let device_name = Path::new(device_info.path().to_str().ok()?).file_name()?;
+
+    let hid_device_path = Path::new("/sys/class/hidraw")
+        .join(device_name)
+        .join("device")
+        .canonicalize()
+        .ok()?;
+
+    hid_device_path
+        .parent()
+        .and_then(|x| x.parent())
+        .map(|x| x.to_path_buf())
+}
+
+#[cfg(target_os = "windows")]
+fn get_revision(device_info: &DeviceInfo) -> Result<u16, InitializationError> {
+    let device_path = get_hid_path(device_info)?;
+
     let file = std::fs::File::open(device_path)
-        .map_err(|x| InitializationError::ErrorUSB(IoError { error: x }))?;
+        .map_err(|x| InitializationError::from_open_error(device_path, x))?;
 
     let hwnd = HWND(file.as_raw_handle() as isize);
 
@@ -160,8 +315,23 @@ fn get_revision(device_path: &str) -> Result<u16, InitializationError> {
 
     match unsafe { HidD_GetAttributes(hwnd, &mut attributes) != BOOLEAN(0) } {
         true => Ok(attributes.VersionNumber),
-        false => Err(InitializationError::InternalError(
-            "Error getting revision.".to_owned(),
-        )),
+        false => Err(InitializationError::InvalidRevision {
+            path: device_path.to_owned(),
+        }),
     }
 }
+
+#[cfg(target_os = "linux")]
+fn get_revision(device_info: &DeviceInfo) -> Result<u16, InitializationError> {
+    let invalid_revision = || InitializationError::InvalidRevision {
+        path: device_info.path().to_string_lossy().into_owned(),
+    };
+
+    let bcd_device_path = get_usb_device_path(device_info)
+        .ok_or_else(invalid_revision)?
+        .join("bcdDevice");
+
+    let bcd_device = std::fs::read_to_string(bcd_device_path).map_err(|_| invalid_revision())?;
+
+    u16::from_str_radix(bcd_device.trim(), 16).map_err(|_| invalid_revision())
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::delay::delay_service;
 use std::time::Duration;
 use std::{fmt, thread};
 
@@ -27,6 +29,24 @@ impl embedded_hal::delay::DelayNs for Delay {
     }
 }
 
+#[cfg(feature = "embedded-hal-async")]
+impl embedded_hal_async::delay::DelayNs for Delay {
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        delay_service::delay(Duration::from_nanos(ns as u64)).await
+    }
+
+    #[inline]
+    async fn delay_us(&mut self, us: u32) {
+        delay_service::delay(Duration::from_micros(us as u64)).await
+    }
+
+    #[inline]
+    async fn delay_ms(&mut self, ms: u32) {
+        delay_service::delay(Duration::from_millis(ms as u64)).await
+    }
+}
+
 #[cfg(feature = "embedded-hal-0")]
 impl embedded_hal_0::blocking::delay::DelayMs<u8> for Delay {
     #[inline]
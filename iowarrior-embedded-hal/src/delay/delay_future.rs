@@ -0,0 +1,39 @@
+use crate::delay::delay_service;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+#[derive(Debug)]
+pub struct DelayFuture {
+    pub(crate) delay_id: usize,
+    pub(crate) deadline: Instant,
+}
+
+impl fmt::Display for DelayFuture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Drop for DelayFuture {
+    #[inline]
+    fn drop(&mut self) {
+        delay_service::remove_delay(self.delay_id);
+    }
+}
+
+impl Future for DelayFuture {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        delay_service::register_delay(self.delay_id, self.deadline, context.waker());
+
+        Poll::Pending
+    }
+}
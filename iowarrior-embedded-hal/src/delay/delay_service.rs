@@ -0,0 +1,101 @@
+use crate::delay::DelayFuture;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::task::Waker;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// All async delays of the process share one timer thread.
+static DELAY_TIMERS: Mutex<DelayTimers> = Mutex::new(DelayTimers {
+    timers: Vec::new(),
+    thread_started: false,
+});
+
+static DELAY_CONDVAR: Condvar = Condvar::new();
+
+static NEXT_DELAY_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+struct DelayTimers {
+    timers: Vec<DelayTimer>,
+    thread_started: bool,
+}
+
+#[derive(Debug)]
+struct DelayTimer {
+    delay_id: usize,
+    deadline: Instant,
+    waker: Waker,
+}
+
+pub fn delay(duration: Duration) -> DelayFuture {
+    DelayFuture {
+        delay_id: NEXT_DELAY_ID.fetch_add(1, Ordering::Relaxed),
+        deadline: Instant::now() + duration,
+    }
+}
+
+pub fn register_delay(delay_id: usize, deadline: Instant, waker: &Waker) {
+    let mut delay_timers = DELAY_TIMERS.lock().unwrap();
+
+    match delay_timers
+        .timers
+        .iter_mut()
+        .find(|x| x.delay_id == delay_id)
+    {
+        Some(delay_timer) => delay_timer.waker.clone_from(waker),
+        None => delay_timers.timers.push(DelayTimer {
+            delay_id,
+            deadline,
+            waker: waker.clone(),
+        }),
+    }
+
+    if !delay_timers.thread_started {
+        delay_timers.thread_started = thread::Builder::new()
+            .name("iowarrior-delay".to_owned())
+            .spawn(run_delay_timers)
+            .is_ok();
+
+        // Without the timer thread the future has to be polled again.
+        if !delay_timers.thread_started {
+            waker.wake_by_ref();
+        }
+    }
+
+    DELAY_CONDVAR.notify_one();
+}
+
+pub fn remove_delay(delay_id: usize) {
+    DELAY_TIMERS
+        .lock()
+        .unwrap()
+        .timers
+        .retain(|x| x.delay_id != delay_id);
+}
+
+fn run_delay_timers() {
+    let mut delay_timers = DELAY_TIMERS.lock().unwrap();
+
+    loop {
+        let now = Instant::now();
+
+        delay_timers.timers.retain(|x| match x.deadline <= now {
+            true => {
+                x.waker.wake_by_ref();
+                false
+            }
+            false => true,
+        });
+
+        delay_timers = match delay_timers.timers.iter().map(|x| x.deadline).min() {
+            None => DELAY_CONDVAR.wait(delay_timers).unwrap(),
+            Some(deadline) => {
+                DELAY_CONDVAR
+                    .wait_timeout(delay_timers, deadline - now)
+                    .unwrap()
+                    .0
+            }
+        };
+    }
+}
@@ -1,2 +1,9 @@
 mod delay;
+#[cfg(feature = "embedded-hal-async")]
+mod delay_future;
+#[cfg(feature = "embedded-hal-async")]
+pub(crate) mod delay_service;
+
 pub use self::delay::*;
+#[cfg(feature = "embedded-hal-async")]
+pub(crate) use self::delay_future::*;
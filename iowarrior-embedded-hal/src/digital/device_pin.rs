@@ -0,0 +1,17 @@
+use crate::iowarrior::IOWarriorType;
+
+pub trait DevicePin {
+    // None for plain pin numbers, which are valid for every device type.
+    const DEVICE_TYPE: Option<IOWarriorType>;
+
+    fn get_pin_value(&self) -> u8;
+}
+
+impl DevicePin for u8 {
+    const DEVICE_TYPE: Option<IOWarriorType> = None;
+
+    #[inline]
+    fn get_pin_value(&self) -> u8 {
+        *self
+    }
+}
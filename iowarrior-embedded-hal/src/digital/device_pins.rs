@@ -0,0 +1,131 @@
+macro_rules! device_pins {
+    ($device_type:expr, [$(($name:ident, $port:literal, $bit:literal)),* $(,)?]) => {
+        use crate::digital::{digital_service, DevicePin, PinParseError};
+        use crate::iowarrior::{IOWarriorType, Peripheral};
+        use std::fmt;
+        use std::str::FromStr;
+
+        pub const DEVICE_TYPE: IOWarriorType = $device_type;
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub enum Pin {
+            $($name),*
+        }
+
+        pub use self::Pin::*;
+
+        impl Pin {
+            pub const ALL: &'static [Pin] = &[$(Pin::$name),*];
+
+            #[inline]
+            pub const fn get_port(&self) -> u8 {
+                match self {
+                    $(Pin::$name => $port),*
+                }
+            }
+
+            #[inline]
+            pub const fn get_bit(&self) -> u8 {
+                match self {
+                    $(Pin::$name => $bit),*
+                }
+            }
+
+            #[inline]
+            pub const fn get_value(&self) -> u8 {
+                8 * self.get_port() + self.get_bit()
+            }
+
+            #[inline]
+            pub fn from_value(value: u8) -> Option<Pin> {
+                Pin::ALL.iter().copied().find(|x| x.get_value() == value)
+            }
+
+            #[inline]
+            pub fn get_peripherals(&self) -> Vec<Peripheral> {
+                digital_service::get_pin_peripherals(DEVICE_TYPE, self.get_value())
+            }
+        }
+
+        impl DevicePin for Pin {
+            const DEVICE_TYPE: Option<IOWarriorType> = Some(DEVICE_TYPE);
+
+            #[inline]
+            fn get_pin_value(&self) -> u8 {
+                self.get_value()
+            }
+        }
+
+        impl From<Pin> for u8 {
+            fn from(pin: Pin) -> u8 {
+                pin.get_value()
+            }
+        }
+
+        impl FromStr for Pin {
+            type Err = PinParseError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let (port, bit) = digital_service::parse_pin_name(s)?;
+
+                Pin::ALL
+                    .iter()
+                    .copied()
+                    .find(|x| x.get_port() == port && x.get_bit() == bit)
+                    .ok_or(PinParseError::PinNotExisting)
+            }
+        }
+
+        impl fmt::Display for Pin {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "P{}.{}", self.get_port(), self.get_bit())
+            }
+        }
+    };
+}
+
+pub(crate) use device_pins;
+
+#[cfg(test)]
+mod tests {
+    use crate::digital::{iow100, iow24, iow24pv, PinParseError};
+    use crate::iowarrior::Peripheral;
+
+    #[test]
+    fn pin_names_round_trip() {
+        for pin in iow100::Pin::ALL {
+            assert_eq!(pin.to_string().parse::<iow100::Pin>().unwrap(), *pin);
+        }
+
+        assert_eq!(iow100::P10_4.to_string(), "P10.4");
+    }
+
+    #[test]
+    fn pin_names_outside_device_are_rejected() {
+        assert!(matches!(
+            "P1.7".parse::<iow24pv::Pin>(),
+            Err(PinParseError::PinNotExisting)
+        ));
+        assert!(matches!(
+            "P2.0".parse::<iow24::Pin>(),
+            Err(PinParseError::PinNotExisting)
+        ));
+    }
+
+    #[test]
+    fn pin_values_round_trip() {
+        for pin in iow24::Pin::ALL {
+            assert_eq!(iow24::Pin::from_value(pin.get_value()), Some(*pin));
+        }
+
+        assert_eq!(iow24::P1_7.get_value(), 15);
+        assert_eq!(iow24::Pin::from_value(16), None);
+    }
+
+    #[test]
+    fn pin_peripherals_are_listed() {
+        assert!(iow24::P0_1.get_peripherals().contains(&Peripheral::I2C));
+        assert!(iow100::P10_4.get_peripherals().contains(&Peripheral::I2C));
+        assert!(iow24::P1_7.get_peripherals().is_empty());
+    }
+}
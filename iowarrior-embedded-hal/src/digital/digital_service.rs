@@ -1,52 +1,94 @@
+use crate::adc::adc_service;
 use crate::bits::Bit;
 use crate::bits::Bitmasking;
-use crate::communication::communication_service;
-use crate::digital::{InputPin, OutputPin, PinError, PinSetupError};
+#[cfg(feature = "embedded-hal-async")]
+use crate::communication::blocking_service;
+use crate::communication::TransportError;
+use crate::digital::{DevicePin, InputPin, OutputPin, PinBatch, PinError, PinEvent, PinGroup};
+use crate::digital::{PinListener, PinParseError, PinSetupError};
+use crate::i2c::i2c_service;
 use crate::iowarrior::{
-    peripheral_service, IOWarriorData, IOWarriorMutData, IOWarriorType, Pipe, UsedPin,
+    iowarrior_service, peripheral_service, IOWarriorData, IOWarriorMutData, IOWarriorType,
+    Peripheral, Pipe, Report, UsedPin,
 };
+use crate::pwm::pwm_service;
+use crate::spi::spi_service;
 use embedded_hal::digital::PinState;
-use std::cell::{RefCell, RefMut};
-use std::rc::Rc;
+#[cfg(feature = "embedded-hal-async")]
+use std::sync::atomic::Ordering;
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MAX_PIN_GROUP_SIZE: usize = 64;
+
+static NEXT_PIN_LISTENER_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Bounds how long a wait keeps its thread after the future was dropped.
+#[cfg(feature = "embedded-hal-async")]
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 pub fn new_input(
-    data: &Rc<IOWarriorData>,
-    mut_data_refcell: &Rc<RefCell<IOWarriorMutData>>,
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
     pin: u8,
 ) -> Result<InputPin, PinSetupError> {
-    let mut mut_data = mut_data_refcell.borrow_mut();
+    let mut mut_data = mut_data_mutex.lock().unwrap();
 
-    enable_gpio(&data, &mut mut_data, PinState::High, pin)?;
+    enable_gpios(&data, &mut mut_data, &[(pin, PinState::High)])?;
 
     Ok(InputPin {
         pin,
         data: data.clone(),
-        mut_data_refcell: mut_data_refcell.clone(),
+        mut_data_mutex: mut_data_mutex.clone(),
     })
 }
 
 pub fn new_output(
-    data: &Rc<IOWarriorData>,
-    mut_data_refcell: &Rc<RefCell<IOWarriorMutData>>,
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
     pin_state: PinState,
     pin: u8,
 ) -> Result<OutputPin, PinSetupError> {
-    let mut mut_data = mut_data_refcell.borrow_mut();
+    let mut mut_data = mut_data_mutex.lock().unwrap();
 
-    enable_gpio(&data, &mut mut_data, pin_state, pin)?;
+    enable_gpios(&data, &mut mut_data, &[(pin, pin_state)])?;
 
     Ok(OutputPin {
         pin,
         data: data.clone(),
-        mut_data_refcell: mut_data_refcell.clone(),
+        mut_data_mutex: mut_data_mutex.clone(),
+    })
+}
+
+pub fn new_pin_group(
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
+    pins: &[u8],
+) -> Result<PinGroup, PinSetupError> {
+    if pins.len() > MAX_PIN_GROUP_SIZE {
+        return Err(PinSetupError::TooManyPins);
+    }
+
+    let mut mut_data = mut_data_mutex.lock().unwrap();
+
+    let pin_states: Vec<_> = pins.iter().map(|pin| (*pin, PinState::High)).collect();
+
+    enable_gpios(data, &mut mut_data, &pin_states)?;
+
+    Ok(PinGroup {
+        pins: pins.to_vec(),
+        data: data.clone(),
+        mut_data_mutex: mut_data_mutex.clone(),
     })
 }
 
-fn enable_gpio(
+fn enable_gpios(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
-    pin_state: PinState,
-    pin: u8,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    pin_states: &[(u8, PinState)],
 ) -> Result<(), PinSetupError> {
     if data.device_type == IOWarriorType::IOWarrior28Dongle
         || data.device_type == IOWarriorType::IOWarrior56Dongle
@@ -54,77 +96,352 @@ fn enable_gpio(
         return Err(PinSetupError::NotSupported);
     }
 
-    if !get_is_valid_gpio(data.device_type, pin) {
-        return Err(PinSetupError::PinNotExisting);
-    }
+    for (index, (pin, _)) in pin_states.iter().enumerate() {
+        if !get_is_valid_gpio(data.device_type, *pin) {
+            return Err(PinSetupError::PinNotExisting);
+        }
 
-    match mut_data.pins_in_use.iter().filter(|x| x.pin == pin).next() {
-        None => {}
-        Some(used_pin) => {
-            return Err(match used_pin.peripheral {
-                None => PinSetupError::AlreadySetup,
-                Some(peripheral) => PinSetupError::BlockedByPeripheral(peripheral),
-            })
+        if pin_states[..index].iter().any(|(x, _)| x == pin) {
+            return Err(PinSetupError::AlreadySetup);
+        }
+
+        match mut_data.pins_in_use.iter().filter(|x| x.pin == *pin).next() {
+            None => {}
+            Some(used_pin) => {
+                return Err(match used_pin.peripheral {
+                    None => PinSetupError::AlreadySetup,
+                    Some(peripheral) => PinSetupError::BlockedByPeripheral(peripheral),
+                })
+            }
         }
     }
 
-    peripheral_service::cleanup_dangling_modules(&data, mut_data)
-        .map_err(|x| PinSetupError::ErrorUSB(x))?;
+    peripheral_service::cleanup_dangling_modules(&data, mut_data).map_err(PinSetupError::from)?;
 
-    peripheral_service::set_pin_output(&data, mut_data, pin_state, pin)
-        .map_err(|x| PinSetupError::ErrorUSB(x))?;
+    peripheral_service::set_pins_output(&data, mut_data, pin_states)
+        .map_err(PinSetupError::from)?;
 
-    mut_data.pins_in_use.push(UsedPin {
-        pin,
-        peripheral: None,
-    });
+    mut_data
+        .pins_in_use
+        .extend(pin_states.iter().map(|(pin, _)| UsedPin {
+            pin: *pin,
+            peripheral: None,
+        }));
 
     Ok(())
 }
 
+// The IOWarrior sends an input report whenever a pin changes, the input state is the one of the
+// newest queued report. With ioctrl, usbhid and libusb the reports are queued by the operating
+// system, with iowkit by the library itself, so the state is current as long as that queue did
+// not overflow. Replayed traces return the state that was recorded at this point of the trace.
 pub fn is_pin_input_state(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     pin: u8,
     expected_pin_state: PinState,
 ) -> Result<bool, PinError> {
-    let report = communication_service::read_report_non_blocking(
-        &mut mut_data.communication_data,
+    drain_pin_reports(data, mut_data).map_err(PinError::from)?;
+
+    let value = get_pin_value(&mut_data.pins_read_report, pin);
+
+    Ok(match expected_pin_state {
+        PinState::Low => !value,
+        PinState::High => value,
+    })
+}
+
+pub fn drain_pin_reports(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<usize, TransportError> {
+    let mut report_count = 0;
+
+    while let Some(report) = iowarrior_service::read_report_non_blocking(
+        data,
+        mut_data,
         data.create_report(Pipe::IOPins),
-    )
-    .map_err(|x| PinError::ErrorUSB(x))?;
+    )? {
+        update_pins_read_report(mut_data, report);
+        report_count += 1;
+    }
 
-    match report {
-        None => {}
-        Some(report) => {
-            mut_data.pins_read_report = report;
-        }
-    };
+    Ok(report_count)
+}
+
+pub fn add_pin_listener(
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    pins: Vec<u8>,
+) -> (usize, Receiver<PinEvent>) {
+    let listener_id = NEXT_PIN_LISTENER_ID.fetch_add(1, atomic::Ordering::Relaxed);
+    let (event_sender, event_receiver) = mpsc::channel();
 
+    mut_data.pin_listeners.push(PinListener {
+        listener_id,
+        pins,
+        event_sender,
+    });
+
+    (listener_id, event_receiver)
+}
+
+pub fn remove_pin_listener(mut_data: &mut MutexGuard<IOWarriorMutData>, listener_id: usize) {
+    mut_data
+        .pin_listeners
+        .retain(|x| x.listener_id != listener_id);
+}
+
+// Polls instead of blocking in a read, so the device lock is not held while waiting.
+pub fn wait_for_pin_report(
+    data: &IOWarriorData,
+    mut_data_mutex: &Mutex<IOWarriorMutData>,
+    poll_interval: Duration,
+) -> Result<(), TransportError> {
+    let report_count = drain_pin_reports(data, &mut mut_data_mutex.lock().unwrap())?;
+
+    if report_count == 0 {
+        thread::sleep(poll_interval);
+    }
+
+    Ok(())
+}
+
+fn update_pins_read_report(mut_data: &mut MutexGuard<IOWarriorMutData>, report: Report) {
+    let timestamp = Instant::now();
+    let previous_report = std::mem::replace(&mut mut_data.pins_read_report, report);
+
+    let IOWarriorMutData {
+        pins_in_use,
+        pins_read_report,
+        pin_listeners,
+        ..
+    } = &mut **mut_data;
+
+    pin_listeners.retain(|pin_listener| {
+        pin_listener
+            .pins
+            .iter()
+            .filter(|&&pin| {
+                pins_in_use
+                    .iter()
+                    .any(|x| x.pin == pin && x.peripheral.is_none())
+            })
+            .filter(|&&pin| {
+                get_pin_value(&previous_report, pin) != get_pin_value(pins_read_report, pin)
+            })
+            .all(|&pin| {
+                pin_listener
+                    .event_sender
+                    .send(PinEvent {
+                        pin,
+                        pin_state: PinState::from(get_pin_value(pins_read_report, pin)),
+                        timestamp,
+                    })
+                    .is_ok()
+            })
+    });
+}
+
+fn get_pin_value(report: &Report, pin: u8) -> bool {
     let byte_index = ((pin as usize) / 8usize) + 1;
     let bit_index = Bit::from_u8(pin % 8u8);
 
-    let value = mut_data.pins_read_report.buffer[byte_index].get_bit(bit_index);
+    report.buffer[byte_index].get_bit(bit_index)
+}
 
-    Ok(match expected_pin_state {
-        PinState::Low => !value,
-        PinState::High => value,
-    })
+#[cfg(feature = "embedded-hal-async")]
+pub async fn wait_for_pin_state(
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
+    pin: u8,
+    expected_pin_state: PinState,
+) -> Result<(), PinError> {
+    let data = data.clone();
+    let mut_data_mutex = mut_data_mutex.clone();
+
+    let (listener_id, event_receiver) =
+        add_pin_listener(&mut mut_data_mutex.lock().unwrap(), vec![pin]);
+
+    let blocking_task = blocking_service::spawn_blocking({
+        let mut_data_mutex = mut_data_mutex.clone();
+
+        move |cancelled| {
+            let result = (|| {
+                if is_pin_input_state(
+                    &data,
+                    &mut mut_data_mutex.lock().unwrap(),
+                    pin,
+                    expected_pin_state,
+                )? {
+                    return Ok(());
+                }
+
+                while !cancelled.load(Ordering::Relaxed) {
+                    wait_for_pin_report(&data, &mut_data_mutex, WAIT_POLL_INTERVAL)
+                        .map_err(PinError::from)?;
+
+                    if event_receiver
+                        .try_iter()
+                        .any(|x| x.pin_state == expected_pin_state)
+                    {
+                        break;
+                    }
+                }
+
+                Ok(())
+            })();
+
+            remove_pin_listener(&mut mut_data_mutex.lock().unwrap(), listener_id);
+
+            result
+        }
+    });
+
+    match blocking_task {
+        Ok(blocking_task) => blocking_task.await,
+        Err(error) => {
+            remove_pin_listener(&mut mut_data_mutex.lock().unwrap(), listener_id);
+            Err(PinError::from(error))
+        }
+    }
+}
+
+#[cfg(feature = "embedded-hal-async")]
+pub async fn wait_for_edge(
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
+    pin: u8,
+    expected_pin_state: Option<PinState>,
+) -> Result<(), PinError> {
+    let data = data.clone();
+    let mut_data_mutex = mut_data_mutex.clone();
+
+    let (listener_id, event_receiver) = {
+        let mut mut_data = mut_data_mutex.lock().unwrap();
+
+        drain_pin_reports(&data, &mut mut_data).map_err(PinError::from)?;
+        add_pin_listener(&mut mut_data, vec![pin])
+    };
+
+    let blocking_task = blocking_service::spawn_blocking({
+        let mut_data_mutex = mut_data_mutex.clone();
+
+        move |cancelled| {
+            let result = (|| {
+                while !cancelled.load(Ordering::Relaxed) {
+                    wait_for_pin_report(&data, &mut_data_mutex, WAIT_POLL_INTERVAL)
+                        .map_err(PinError::from)?;
+
+                    if event_receiver
+                        .try_iter()
+                        .any(|x| expected_pin_state.is_none_or(|y| x.pin_state == y))
+                    {
+                        break;
+                    }
+                }
+
+                Ok(())
+            })();
+
+            remove_pin_listener(&mut mut_data_mutex.lock().unwrap(), listener_id);
+
+            result
+        }
+    });
+
+    match blocking_task {
+        Ok(blocking_task) => blocking_task.await,
+        Err(error) => {
+            remove_pin_listener(&mut mut_data_mutex.lock().unwrap(), listener_id);
+            Err(PinError::from(error))
+        }
+    }
+}
+
+pub fn get_pin_group_input_state(
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    pins: &[u8],
+) -> Result<u64, PinError> {
+    drain_pin_reports(data, mut_data).map_err(PinError::from)?;
+
+    Ok(get_pin_group_value(&mut_data.pins_read_report, pins))
+}
+
+pub fn set_pin_group_output_state(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    pins: &[u8],
+    value: u64,
+    mask: u64,
+) -> Result<(), PinError> {
+    let pin_states: Vec<_> = pins
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| mask & (1u64 << index) != 0)
+        .map(|(index, pin)| (*pin, PinState::from(value & (1u64 << index) != 0)))
+        .collect();
+
+    peripheral_service::set_pins_output(data, mut_data, &pin_states).map_err(PinError::from)
+}
+
+pub fn get_pin_group_output_state(mut_data: &MutexGuard<IOWarriorMutData>, pins: &[u8]) -> u64 {
+    get_pin_group_value(&mut_data.pins_write_report, pins)
+}
+
+fn get_pin_group_value(report: &Report, pins: &[u8]) -> u64 {
+    pins.iter()
+        .enumerate()
+        .filter(|(_, pin)| get_pin_value(report, **pin))
+        .fold(0u64, |value, (index, _)| value | (1u64 << index))
+}
+
+pub fn new_pin_batch(
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
+) -> PinBatch {
+    mut_data_mutex.lock().unwrap().pins_write_batches += 1;
+
+    PinBatch {
+        data: data.clone(),
+        mut_data_mutex: mut_data_mutex.clone(),
+    }
+}
+
+pub fn flush_pin_batch(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<(), PinError> {
+    peripheral_service::flush_pins_output(data, mut_data).map_err(PinError::from)
+}
+
+pub fn end_pin_batch(data: &IOWarriorData, mut_data: &mut MutexGuard<IOWarriorMutData>) {
+    mut_data.pins_write_batches -= 1;
+
+    if mut_data.pins_write_batches == 0 {
+        match peripheral_service::flush_pins_output(data, mut_data) {
+            Ok(_) => {}
+            Err(_) => {
+                // Drop can't report the error, so the pins go back to the last written state.
+                // PinBatch::flush reports the error instead.
+                peripheral_service::rollback_pins_output(mut_data);
+            }
+        };
+    }
 }
 
 pub fn set_pin_output_state(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     pin: u8,
     pin_state: PinState,
 ) -> Result<(), PinError> {
-    peripheral_service::set_pin_output(data, mut_data, pin_state, pin)
-        .map_err(|x| PinError::ErrorUSB(x))
+    peripheral_service::set_pin_output(data, mut_data, pin_state, pin).map_err(PinError::from)
 }
 
 pub fn is_pin_output_state(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     pin: u8,
     expected_pin_state: PinState,
 ) -> Result<bool, PinError> {
@@ -139,7 +456,69 @@ pub fn is_pin_output_state(
     })
 }
 
-fn get_is_valid_gpio(device_type: IOWarriorType, pin: u8) -> bool {
+pub fn get_pin_peripherals(device_type: IOWarriorType, pin: u8) -> Vec<Peripheral> {
+    [
+        (
+            Peripheral::I2C,
+            i2c_service::get_alternate_pins(device_type),
+        ),
+        (
+            Peripheral::PWM,
+            pwm_service::get_alternate_pins(device_type),
+        ),
+        (
+            Peripheral::SPI,
+            spi_service::get_alternate_pins(device_type),
+        ),
+        (
+            Peripheral::ADC,
+            adc_service::get_alternate_pins(device_type),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, pins)| pins.contains(&pin))
+    .map(|(peripheral, _)| peripheral)
+    .collect()
+}
+
+pub fn get_device_pin_value<T: DevicePin>(
+    device_type: IOWarriorType,
+    pin: T,
+) -> Result<u8, PinSetupError> {
+    match T::DEVICE_TYPE {
+        Some(pin_device_type) if pin_device_type != device_type => {
+            Err(PinSetupError::PinNotExisting)
+        }
+        _ => Ok(pin.get_pin_value()),
+    }
+}
+
+// Accepts exactly the names written by Display, e.g. "P10.4".
+pub fn parse_pin_name(name: &str) -> Result<(u8, u8), PinParseError> {
+    let (port, bit) = name
+        .strip_prefix('P')
+        .and_then(|x| x.split_once('.'))
+        .ok_or(PinParseError::InvalidFormat)?;
+
+    let port = parse_pin_number(port)?;
+    let bit = parse_pin_number(bit)?;
+
+    match bit < 8 {
+        true => Ok((port, bit)),
+        false => Err(PinParseError::InvalidFormat),
+    }
+}
+
+fn parse_pin_number(number: &str) -> Result<u8, PinParseError> {
+    match !number.is_empty() && number.bytes().all(|x| x.is_ascii_digit()) {
+        true => number
+            .parse::<u8>()
+            .map_err(|_| PinParseError::InvalidFormat),
+        false => Err(PinParseError::InvalidFormat),
+    }
+}
+
+pub fn get_is_valid_gpio(device_type: IOWarriorType, pin: u8) -> bool {
     match device_type {
         IOWarriorType::IOWarrior40 => pin < 32,
         IOWarriorType::IOWarrior24 => pin < 16,
@@ -153,3 +532,175 @@ fn get_is_valid_gpio(device_type: IOWarriorType, pin: u8) -> bool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::sim::initialization_service::get_test_iowarrior;
+
+    #[test]
+    fn pin_group_maps_bits_to_pins() {
+        let (iowarrior, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        let pins = [crate::pin!(0, 3), crate::pin!(0, 0), crate::pin!(1, 2)];
+        let pin_group = iowarrior.setup_pin_group(&pins).unwrap();
+
+        pin_group.write(0b010).unwrap();
+        assert_eq!(pin_group.get_output(), 0b010);
+        assert_eq!(simulation.get_output_pin(pins[0]).unwrap(), PinState::Low);
+        assert_eq!(simulation.get_output_pin(pins[1]).unwrap(), PinState::High);
+        assert_eq!(simulation.get_output_pin(pins[2]).unwrap(), PinState::Low);
+
+        pin_group.write_masked(0b101, 0b100).unwrap();
+        assert_eq!(pin_group.get_output(), 0b110);
+        assert_eq!(simulation.get_output_pin(pins[0]).unwrap(), PinState::Low);
+        assert_eq!(simulation.get_output_pin(pins[2]).unwrap(), PinState::High);
+
+        pin_group.write(0b111).unwrap();
+        simulation.set_input_pin(pins[0], PinState::Low).unwrap();
+        assert_eq!(pin_group.read().unwrap(), 0b110);
+    }
+
+    #[test]
+    fn pin_group_rejects_duplicate_pins() {
+        let (iowarrior, _) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        assert!(matches!(
+            iowarrior.setup_pin_group(&[1, 2, 1]),
+            Err(PinSetupError::AlreadySetup)
+        ));
+        assert!(iowarrior.setup_pin_group(&[1, 2]).is_ok());
+    }
+
+    #[test]
+    fn drain_pin_reports_returns_on_empty_queue() {
+        let (iowarrior, _) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        let mut mut_data = iowarrior.mut_data_mutex.lock().unwrap();
+
+        drain_pin_reports(&iowarrior.data, &mut mut_data).unwrap();
+        drain_pin_reports(&iowarrior.data, &mut mut_data).unwrap();
+    }
+
+    #[test]
+    fn is_pin_input_state_returns_newest_report() {
+        let (iowarrior, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        let input_pin = iowarrior.setup_input(3).unwrap();
+
+        for pin_state in [PinState::Low, PinState::High, PinState::Low] {
+            simulation.set_input_pin(3, pin_state).unwrap();
+        }
+
+        let mut mut_data = input_pin.mut_data_mutex.lock().unwrap();
+
+        assert!(is_pin_input_state(&input_pin.data, &mut mut_data, 3, PinState::Low).unwrap());
+        assert!(is_pin_input_state(&input_pin.data, &mut mut_data, 3, PinState::Low).unwrap());
+    }
+
+    #[test]
+    fn failed_batch_flush_rolls_back_on_drop() {
+        use crate::communication::DisconnectedTransport;
+        use embedded_hal::digital::{OutputPin, StatefulOutputPin};
+
+        let (iowarrior, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        let mut output_pin = iowarrior.setup_output_as_high(4).unwrap();
+        let pin_batch = iowarrior.start_pin_batch();
+
+        output_pin.set_low().unwrap();
+
+        assert!(output_pin.is_set_low().unwrap());
+        assert_eq!(simulation.get_output_pin(4).unwrap(), PinState::High);
+
+        iowarrior.mut_data_mutex.lock().unwrap().communication_data =
+            Box::new(DisconnectedTransport::default());
+
+        assert!(matches!(pin_batch.flush(), Err(PinError::Disconnected)));
+        assert!(output_pin.is_set_low().unwrap());
+
+        drop(pin_batch);
+
+        assert!(output_pin.is_set_high().unwrap());
+    }
+
+    #[test]
+    fn batch_flush_writes_pending_pins() {
+        use embedded_hal::digital::OutputPin;
+
+        let (iowarrior, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        let mut output_pin = iowarrior.setup_output_as_high(4).unwrap();
+        let pin_batch = iowarrior.start_pin_batch();
+
+        output_pin.set_low().unwrap();
+        pin_batch.flush().unwrap();
+
+        assert_eq!(simulation.get_output_pin(4).unwrap(), PinState::Low);
+    }
+
+    #[test]
+    fn parse_pin_name_accepts_display_format() {
+        assert_eq!(parse_pin_name("P0.0").unwrap(), (0, 0));
+        assert_eq!(parse_pin_name("P10.7").unwrap(), (10, 7));
+    }
+
+    #[test]
+    fn parse_pin_name_rejects_other_formats() {
+        for name in [
+            "", "P", "P1", "P1.", "P.1", "p1.2", " P1.2", "P1.2 ", "P+1.2", "P1.-2", "P1.8",
+            "P256.0", "P1.2.3", "1.2",
+        ] {
+            assert!(
+                matches!(parse_pin_name(name), Err(PinParseError::InvalidFormat)),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn get_device_pin_value_checks_device_type() {
+        use crate::digital::{iow100, iow24};
+
+        assert_eq!(
+            get_device_pin_value(IOWarriorType::IOWarrior24, iow24::P0_3).unwrap(),
+            3
+        );
+        assert_eq!(
+            get_device_pin_value(IOWarriorType::IOWarrior24, 84u8).unwrap(),
+            84
+        );
+        assert!(matches!(
+            get_device_pin_value(IOWarriorType::IOWarrior24, iow100::P10_4),
+            Err(PinSetupError::PinNotExisting)
+        ));
+    }
+
+    #[test]
+    fn setup_input_rejects_pin_of_other_device() {
+        use crate::digital::iow100;
+
+        let (iowarrior, _) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        assert!(matches!(
+            iowarrior.setup_input(iow100::P1_0),
+            Err(PinSetupError::PinNotExisting)
+        ));
+    }
+
+    #[test]
+    fn setup_pin_group_rejects_pins_of_other_device() {
+        use crate::digital::{iow100, iow24};
+
+        let (iowarrior, _) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        assert!(matches!(
+            iowarrior.setup_pin_group(&[iow100::P1_0, iow100::P1_1]),
+            Err(PinSetupError::PinNotExisting)
+        ));
+        assert!(iowarrior
+            .setup_pin_group(&[iow24::P0_0, iow24::P0_1])
+            .is_ok());
+    }
+}
@@ -2,14 +2,13 @@ use crate::digital::digital_service;
 use crate::digital::PinError;
 use crate::iowarrior::{peripheral_service, IOWarriorData, IOWarriorMutData};
 use embedded_hal::digital::PinState;
-use std::cell::RefCell;
 use std::fmt;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug)]
 pub struct InputPin {
-    pub(crate) data: Rc<IOWarriorData>,
-    pub(crate) mut_data_refcell: Rc<RefCell<IOWarriorMutData>>,
+    pub(crate) data: Arc<IOWarriorData>,
+    pub(crate) mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
     pub(crate) pin: u8,
 }
 
@@ -22,7 +21,7 @@ impl embedded_hal::digital::InputPin for InputPin {
     fn is_high(&mut self) -> Result<bool, Self::Error> {
         digital_service::is_pin_input_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::High,
         )
@@ -32,7 +31,7 @@ impl embedded_hal::digital::InputPin for InputPin {
     fn is_low(&mut self) -> Result<bool, Self::Error> {
         digital_service::is_pin_input_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::Low,
         )
@@ -47,7 +46,7 @@ impl embedded_hal_0::digital::v2::InputPin for InputPin {
     fn is_high(&self) -> Result<bool, Self::Error> {
         digital_service::is_pin_input_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::High,
         )
@@ -57,13 +56,65 @@ impl embedded_hal_0::digital::v2::InputPin for InputPin {
     fn is_low(&self) -> Result<bool, Self::Error> {
         digital_service::is_pin_input_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::Low,
         )
     }
 }
 
+#[cfg(feature = "embedded-hal-async")]
+impl embedded_hal_async::digital::Wait for InputPin {
+    #[inline]
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        digital_service::wait_for_pin_state(
+            &self.data,
+            &self.mut_data_mutex,
+            self.pin,
+            PinState::High,
+        )
+        .await
+    }
+
+    #[inline]
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        digital_service::wait_for_pin_state(
+            &self.data,
+            &self.mut_data_mutex,
+            self.pin,
+            PinState::Low,
+        )
+        .await
+    }
+
+    #[inline]
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        digital_service::wait_for_edge(
+            &self.data,
+            &self.mut_data_mutex,
+            self.pin,
+            Some(PinState::High),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        digital_service::wait_for_edge(
+            &self.data,
+            &self.mut_data_mutex,
+            self.pin,
+            Some(PinState::Low),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        digital_service::wait_for_edge(&self.data, &self.mut_data_mutex, self.pin, None).await
+    }
+}
+
 impl fmt::Display for InputPin {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}", self)
@@ -75,7 +126,7 @@ impl Drop for InputPin {
     fn drop(&mut self) {
         peripheral_service::disable_gpio(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
         );
     }
@@ -0,0 +1,91 @@
+use crate::digital::device_pins::device_pins;
+
+device_pins!(
+    IOWarriorType::IOWarrior100,
+    [
+        (P0_0, 0, 0),
+        (P0_1, 0, 1),
+        (P0_2, 0, 2),
+        (P0_3, 0, 3),
+        (P0_4, 0, 4),
+        (P0_5, 0, 5),
+        (P0_6, 0, 6),
+        (P0_7, 0, 7),
+        (P1_0, 1, 0),
+        (P1_1, 1, 1),
+        (P1_2, 1, 2),
+        (P2_0, 2, 0),
+        (P2_1, 2, 1),
+        (P2_2, 2, 2),
+        (P2_3, 2, 3),
+        (P2_4, 2, 4),
+        (P2_5, 2, 5),
+        (P2_6, 2, 6),
+        (P2_7, 2, 7),
+        (P3_0, 3, 0),
+        (P3_1, 3, 1),
+        (P3_2, 3, 2),
+        (P3_3, 3, 3),
+        (P3_4, 3, 4),
+        (P3_5, 3, 5),
+        (P3_6, 3, 6),
+        (P3_7, 3, 7),
+        (P4_0, 4, 0),
+        (P4_1, 4, 1),
+        (P4_2, 4, 2),
+        (P4_3, 4, 3),
+        (P4_4, 4, 4),
+        (P4_5, 4, 5),
+        (P4_6, 4, 6),
+        (P4_7, 4, 7),
+        (P5_0, 5, 0),
+        (P5_1, 5, 1),
+        (P5_2, 5, 2),
+        (P5_3, 5, 3),
+        (P5_4, 5, 4),
+        (P5_5, 5, 5),
+        (P5_6, 5, 6),
+        (P5_7, 5, 7),
+        (P6_0, 6, 0),
+        (P6_1, 6, 1),
+        (P6_2, 6, 2),
+        (P6_3, 6, 3),
+        (P6_4, 6, 4),
+        (P6_5, 6, 5),
+        (P6_6, 6, 6),
+        (P6_7, 6, 7),
+        (P7_0, 7, 0),
+        (P7_1, 7, 1),
+        (P7_2, 7, 2),
+        (P7_3, 7, 3),
+        (P7_4, 7, 4),
+        (P7_5, 7, 5),
+        (P7_6, 7, 6),
+        (P7_7, 7, 7),
+        (P8_0, 8, 0),
+        (P8_1, 8, 1),
+        (P8_2, 8, 2),
+        (P8_3, 8, 3),
+        (P8_4, 8, 4),
+        (P8_5, 8, 5),
+        (P8_6, 8, 6),
+        (P8_7, 8, 7),
+        (P9_0, 9, 0),
+        (P9_1, 9, 1),
+        (P9_2, 9, 2),
+        (P9_3, 9, 3),
+        (P9_4, 9, 4),
+        (P9_5, 9, 5),
+        (P9_6, 9, 6),
+        (P9_7, 9, 7),
+        (P10_0, 10, 0),
+        (P10_1, 10, 1),
+        (P10_2, 10, 2),
+        (P10_3, 10, 3),
+        (P10_4, 10, 4),
+        (P10_5, 10, 5),
+        (P10_6, 10, 6),
+        (P11_1, 11, 1),
+        (P11_2, 11, 2),
+    ]
+);
@@ -0,0 +1,23 @@
+use crate::digital::device_pins::device_pins;
+
+device_pins!(
+    IOWarriorType::IOWarrior24,
+    [
+        (P0_0, 0, 0),
+        (P0_1, 0, 1),
+        (P0_2, 0, 2),
+        (P0_3, 0, 3),
+        (P0_4, 0, 4),
+        (P0_5, 0, 5),
+        (P0_6, 0, 6),
+        (P0_7, 0, 7),
+        (P1_0, 1, 0),
+        (P1_1, 1, 1),
+        (P1_2, 1, 2),
+        (P1_3, 1, 3),
+        (P1_4, 1, 4),
+        (P1_5, 1, 5),
+        (P1_6, 1, 6),
+        (P1_7, 1, 7),
+    ]
+);
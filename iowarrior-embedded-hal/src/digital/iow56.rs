@@ -0,0 +1,57 @@
+use crate::digital::device_pins::device_pins;
+
+device_pins!(
+    IOWarriorType::IOWarrior56,
+    [
+        (P0_0, 0, 0),
+        (P0_1, 0, 1),
+        (P0_2, 0, 2),
+        (P0_3, 0, 3),
+        (P0_4, 0, 4),
+        (P0_5, 0, 5),
+        (P0_6, 0, 6),
+        (P0_7, 0, 7),
+        (P1_0, 1, 0),
+        (P1_1, 1, 1),
+        (P1_2, 1, 2),
+        (P1_3, 1, 3),
+        (P1_4, 1, 4),
+        (P1_5, 1, 5),
+        (P1_6, 1, 6),
+        (P1_7, 1, 7),
+        (P2_0, 2, 0),
+        (P2_1, 2, 1),
+        (P2_2, 2, 2),
+        (P2_3, 2, 3),
+        (P2_4, 2, 4),
+        (P2_5, 2, 5),
+        (P2_6, 2, 6),
+        (P2_7, 2, 7),
+        (P3_0, 3, 0),
+        (P3_1, 3, 1),
+        (P3_2, 3, 2),
+        (P3_3, 3, 3),
+        (P3_4, 3, 4),
+        (P3_5, 3, 5),
+        (P3_6, 3, 6),
+        (P3_7, 3, 7),
+        (P4_0, 4, 0),
+        (P4_1, 4, 1),
+        (P4_2, 4, 2),
+        (P4_3, 4, 3),
+        (P4_4, 4, 4),
+        (P4_5, 4, 5),
+        (P4_6, 4, 6),
+        (P4_7, 4, 7),
+        (P5_0, 5, 0),
+        (P5_1, 5, 1),
+        (P5_2, 5, 2),
+        (P5_3, 5, 3),
+        (P5_4, 5, 4),
+        (P5_5, 5, 5),
+        (P5_6, 5, 6),
+        (P5_7, 5, 7),
+        (P6_0, 6, 0),
+        (P6_7, 6, 7),
+    ]
+);
@@ -1,10 +1,41 @@
+mod device_pin;
+mod device_pins;
 pub(crate) mod digital_service;
 mod input_pin;
+pub mod iow100;
+pub mod iow24;
+pub mod iow24pv;
+pub mod iow28;
+pub mod iow28l;
+pub mod iow40;
+pub mod iow56;
 mod output_pin;
+mod pin_batch;
 mod pin_error;
+mod pin_event;
+mod pin_group;
+mod pin_listener;
+mod pin_parse_error;
 mod pin_setup_error;
+mod pin_watcher;
+mod pin_watcher_config;
+pub(crate) mod pin_watcher_service;
 
+pub use self::device_pin::*;
 pub use self::input_pin::*;
 pub use self::output_pin::*;
+pub use self::pin_batch::*;
 pub use self::pin_error::*;
+pub use self::pin_event::*;
+pub use self::pin_group::*;
+pub(crate) use self::pin_listener::*;
+pub use self::pin_parse_error::*;
 pub use self::pin_setup_error::*;
+pub use self::pin_watcher::*;
+pub use self::pin_watcher_config::*;
+
+static_assertions::assert_impl_all!(InputPin: Send, Sync);
+static_assertions::assert_impl_all!(OutputPin: Send, Sync);
+static_assertions::assert_impl_all!(PinGroup: Send, Sync);
+static_assertions::assert_impl_all!(PinBatch: Send, Sync);
+static_assertions::assert_impl_all!(PinWatcher: Send, Sync);
@@ -1,14 +1,13 @@
 use crate::digital::{digital_service, PinError};
 use crate::iowarrior::{peripheral_service, IOWarriorData, IOWarriorMutData};
 use embedded_hal::digital::PinState;
-use std::cell::RefCell;
 use std::fmt;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug)]
 pub struct OutputPin {
-    pub(crate) data: Rc<IOWarriorData>,
-    pub(crate) mut_data_refcell: Rc<RefCell<IOWarriorMutData>>,
+    pub(crate) data: Arc<IOWarriorData>,
+    pub(crate) mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
     pub(crate) pin: u8,
 }
 
@@ -21,7 +20,7 @@ impl embedded_hal::digital::OutputPin for OutputPin {
     fn set_low(&mut self) -> Result<(), Self::Error> {
         digital_service::set_pin_output_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::Low,
         )
@@ -31,7 +30,7 @@ impl embedded_hal::digital::OutputPin for OutputPin {
     fn set_high(&mut self) -> Result<(), Self::Error> {
         digital_service::set_pin_output_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::High,
         )
@@ -43,7 +42,7 @@ impl embedded_hal::digital::StatefulOutputPin for OutputPin {
     fn is_set_high(&mut self) -> Result<bool, Self::Error> {
         digital_service::is_pin_output_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::High,
         )
@@ -53,7 +52,7 @@ impl embedded_hal::digital::StatefulOutputPin for OutputPin {
     fn is_set_low(&mut self) -> Result<bool, Self::Error> {
         digital_service::is_pin_output_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::Low,
         )
@@ -68,7 +67,7 @@ impl embedded_hal_0::digital::v2::OutputPin for OutputPin {
     fn set_low(&mut self) -> Result<(), Self::Error> {
         digital_service::set_pin_output_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::Low,
         )
@@ -78,7 +77,7 @@ impl embedded_hal_0::digital::v2::OutputPin for OutputPin {
     fn set_high(&mut self) -> Result<(), Self::Error> {
         digital_service::set_pin_output_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::High,
         )
@@ -91,7 +90,7 @@ impl embedded_hal_0::digital::v2::StatefulOutputPin for OutputPin {
     fn is_set_high(&self) -> Result<bool, Self::Error> {
         digital_service::is_pin_output_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::High,
         )
@@ -101,7 +100,7 @@ impl embedded_hal_0::digital::v2::StatefulOutputPin for OutputPin {
     fn is_set_low(&self) -> Result<bool, Self::Error> {
         digital_service::is_pin_output_state(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
             PinState::Low,
         )
@@ -119,7 +118,7 @@ impl Drop for OutputPin {
     fn drop(&mut self) {
         peripheral_service::disable_gpio(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             self.pin,
         );
     }
@@ -0,0 +1,31 @@
+use crate::digital::digital_service;
+use crate::digital::PinError;
+use crate::iowarrior::{IOWarriorData, IOWarriorMutData};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+pub struct PinBatch {
+    pub(crate) data: Arc<IOWarriorData>,
+    pub(crate) mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
+}
+
+impl fmt::Display for PinBatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl PinBatch {
+    #[inline]
+    pub fn flush(&self) -> Result<(), PinError> {
+        digital_service::flush_pin_batch(&self.data, &mut self.mut_data_mutex.lock().unwrap())
+    }
+}
+
+impl Drop for PinBatch {
+    #[inline]
+    fn drop(&mut self) {
+        digital_service::end_pin_batch(&self.data, &mut self.mut_data_mutex.lock().unwrap());
+    }
+}
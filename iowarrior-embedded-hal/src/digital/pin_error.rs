@@ -1,18 +1,38 @@
+use crate::communication::TransportError;
 use embedded_hal::digital::ErrorKind;
-use hidapi::HidError;
 use thiserror::Error;
 
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum PinError {
-    #[error("USB HID error.")]
-    ErrorUSB(HidError),
+    #[error("USB transport error.")]
+    ErrorUSB(#[source] TransportError),
+    #[error("IOWarrior disconnected.")]
+    Disconnected,
+    #[error("Timeout while waiting for IOWarrior.")]
+    Timeout,
+    #[error("IOWarrior operation cancelled.")]
+    Cancelled,
+}
+
+impl From<TransportError> for PinError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Disconnected => PinError::Disconnected,
+            TransportError::Timeout => PinError::Timeout,
+            TransportError::Cancelled => PinError::Cancelled,
+            error => PinError::ErrorUSB(error),
+        }
+    }
 }
 
 impl embedded_hal::digital::Error for PinError {
     fn kind(&self) -> ErrorKind {
         match self {
-            PinError::ErrorUSB(_) => ErrorKind::Other,
+            PinError::ErrorUSB(_)
+            | PinError::Disconnected
+            | PinError::Timeout
+            | PinError::Cancelled => ErrorKind::Other,
         }
     }
 }
@@ -0,0 +1,75 @@
+use crate::digital::digital_service;
+use crate::digital::PinError;
+use crate::iowarrior::{peripheral_service, IOWarriorData, IOWarriorMutData};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+pub struct PinGroup {
+    pub(crate) data: Arc<IOWarriorData>,
+    pub(crate) mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
+    pub(crate) pins: Vec<u8>,
+}
+
+impl fmt::Display for PinGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl PinGroup {
+    #[inline]
+    pub fn get_pins(&self) -> Vec<u8> {
+        self.pins.clone()
+    }
+
+    #[inline]
+    pub fn read(&self) -> Result<u64, PinError> {
+        digital_service::get_pin_group_input_state(
+            &self.data,
+            &mut self.mut_data_mutex.lock().unwrap(),
+            &self.pins,
+        )
+    }
+
+    #[inline]
+    pub fn write(&self, value: u64) -> Result<(), PinError> {
+        digital_service::set_pin_group_output_state(
+            &self.data,
+            &mut self.mut_data_mutex.lock().unwrap(),
+            &self.pins,
+            value,
+            u64::MAX,
+        )
+    }
+
+    #[inline]
+    pub fn write_masked(&self, value: u64, mask: u64) -> Result<(), PinError> {
+        digital_service::set_pin_group_output_state(
+            &self.data,
+            &mut self.mut_data_mutex.lock().unwrap(),
+            &self.pins,
+            value,
+            mask,
+        )
+    }
+
+    #[inline]
+    pub fn get_output(&self) -> u64 {
+        digital_service::get_pin_group_output_state(
+            &self.mut_data_mutex.lock().unwrap(),
+            &self.pins,
+        )
+    }
+}
+
+impl Drop for PinGroup {
+    #[inline]
+    fn drop(&mut self) {
+        peripheral_service::disable_gpios(
+            &self.data,
+            &mut self.mut_data_mutex.lock().unwrap(),
+            &self.pins,
+        );
+    }
+}
@@ -0,0 +1,16 @@
+use crate::digital::PinEvent;
+use std::fmt;
+use std::sync::mpsc::Sender;
+
+#[derive(Clone, Debug)]
+pub struct PinListener {
+    pub listener_id: usize,
+    pub pins: Vec<u8>,
+    pub event_sender: Sender<PinEvent>,
+}
+
+impl fmt::Display for PinListener {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
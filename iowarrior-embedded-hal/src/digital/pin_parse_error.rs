@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum PinParseError {
+    #[error("Pin name is not in the format P<port>.<bit>.")]
+    InvalidFormat,
+    #[error("Pin not existing.")]
+    PinNotExisting,
+}
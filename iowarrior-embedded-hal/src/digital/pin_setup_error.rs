@@ -1,12 +1,18 @@
+use crate::communication::TransportError;
 use crate::iowarrior::Peripheral;
-use hidapi::HidError;
 use thiserror::Error;
 
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum PinSetupError {
-    #[error("USB HID error.")]
-    ErrorUSB(HidError),
+    #[error("USB transport error.")]
+    ErrorUSB(#[source] TransportError),
+    #[error("IOWarrior disconnected.")]
+    Disconnected,
+    #[error("Timeout while waiting for IOWarrior.")]
+    Timeout,
+    #[error("IOWarrior operation cancelled.")]
+    Cancelled,
     #[error("Pin not existing.")]
     PinNotExisting,
     #[error("Pin already configured.")]
@@ -15,4 +21,21 @@ pub enum PinSetupError {
     BlockedByPeripheral(Peripheral),
     #[error("Pins are not supported by hardware.")]
     NotSupported,
+    #[error("Too many pins for one pin group.")]
+    TooManyPins,
+    #[error("Pin belongs to a different IOWarrior.")]
+    PinFromOtherDevice,
+    #[error("Pin watcher thread could not be started.")]
+    PinWatcherNotStarted(#[source] std::io::Error),
+}
+
+impl From<TransportError> for PinSetupError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Disconnected => PinSetupError::Disconnected,
+            TransportError::Timeout => PinSetupError::Timeout,
+            TransportError::Cancelled => PinSetupError::Cancelled,
+            error => PinSetupError::ErrorUSB(error),
+        }
+    }
 }
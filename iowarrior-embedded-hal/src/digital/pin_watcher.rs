@@ -0,0 +1,66 @@
+use crate::digital::{digital_service, PinError, PinEvent};
+use crate::iowarrior::IOWarriorMutData;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct PinWatcher {
+    pub(crate) mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
+    pub(crate) listener_id: usize,
+    pub(crate) event_receiver: Mutex<Receiver<PinEvent>>,
+    pub(crate) running: Arc<AtomicBool>,
+    pub(crate) error: Arc<Mutex<Option<PinError>>>,
+    pub(crate) thread: Option<JoinHandle<()>>,
+}
+
+impl fmt::Display for PinWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Drop for PinWatcher {
+    #[inline]
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        digital_service::remove_pin_listener(
+            &mut self.mut_data_mutex.lock().unwrap(),
+            self.listener_id,
+        );
+    }
+}
+
+impl PinWatcher {
+    #[inline]
+    pub fn recv(&self) -> Option<PinEvent> {
+        self.event_receiver.lock().unwrap().recv().ok()
+    }
+
+    #[inline]
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<PinEvent> {
+        self.event_receiver
+            .lock()
+            .unwrap()
+            .recv_timeout(timeout)
+            .ok()
+    }
+
+    #[inline]
+    pub fn try_recv(&self) -> Option<PinEvent> {
+        self.event_receiver.lock().unwrap().try_recv().ok()
+    }
+
+    #[inline]
+    pub fn take_error(&self) -> Option<PinError> {
+        self.error.lock().unwrap().take()
+    }
+}
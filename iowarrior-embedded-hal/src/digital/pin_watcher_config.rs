@@ -0,0 +1,21 @@
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PinWatcherConfig {
+    pub poll_interval: Duration,
+}
+
+impl Default for PinWatcherConfig {
+    fn default() -> Self {
+        PinWatcherConfig {
+            poll_interval: Duration::from_millis(10),
+        }
+    }
+}
+
+impl fmt::Display for PinWatcherConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,117 @@
+use crate::digital::{digital_service, InputPin, PinError, PinSetupError};
+use crate::digital::{PinWatcher, PinWatcherConfig};
+use crate::iowarrior::{IOWarriorData, IOWarriorMutData};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub fn create_pin_watcher(
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
+    input_pins: &[&InputPin],
+    config: PinWatcherConfig,
+) -> Result<PinWatcher, PinSetupError> {
+    if input_pins
+        .iter()
+        .any(|x| !Arc::ptr_eq(&x.mut_data_mutex, mut_data_mutex))
+    {
+        return Err(PinSetupError::PinFromOtherDevice);
+    }
+
+    let (listener_id, event_receiver) = digital_service::add_pin_listener(
+        &mut mut_data_mutex.lock().unwrap(),
+        input_pins.iter().map(|x| x.pin).collect(),
+    );
+
+    let running = Arc::new(AtomicBool::new(true));
+    let error = Arc::new(Mutex::new(None));
+
+    let thread = {
+        let data = data.clone();
+        let mut_data_mutex = mut_data_mutex.clone();
+        let running = running.clone();
+        let error = error.clone();
+
+        thread::Builder::new()
+            .name("iowarrior-pin-watcher".to_owned())
+            .spawn(move || watch_pins(config, listener_id, running, error, data, mut_data_mutex))
+    };
+
+    let thread = match thread {
+        Ok(thread) => thread,
+        Err(error) => {
+            digital_service::remove_pin_listener(&mut mut_data_mutex.lock().unwrap(), listener_id);
+
+            return Err(PinSetupError::PinWatcherNotStarted(error));
+        }
+    };
+
+    Ok(PinWatcher {
+        mut_data_mutex: mut_data_mutex.clone(),
+        listener_id,
+        event_receiver: Mutex::new(event_receiver),
+        running,
+        error,
+        thread: Some(thread),
+    })
+}
+
+fn watch_pins(
+    config: PinWatcherConfig,
+    listener_id: usize,
+    running: Arc<AtomicBool>,
+    error: Arc<Mutex<Option<PinError>>>,
+    data: Arc<IOWarriorData>,
+    mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
+) {
+    while running.load(Ordering::Relaxed) {
+        if let Err(transport_error) =
+            digital_service::wait_for_pin_report(&data, &mut_data_mutex, config.poll_interval)
+        {
+            // Dropping the listener closes the channel, so a blocked recv returns.
+            digital_service::remove_pin_listener(&mut mut_data_mutex.lock().unwrap(), listener_id);
+            *error.lock().unwrap() = Some(PinError::from(transport_error));
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::communication::sim::initialization_service::get_test_iowarrior;
+    use crate::iowarrior::IOWarriorType;
+    use embedded_hal::digital::PinState;
+    use std::time::Duration;
+
+    #[test]
+    fn pin_watcher_reports_pin_changes() {
+        let (iowarrior, simulation) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        let input_pin = iowarrior.setup_input(3).unwrap();
+        let pin_watcher = iowarrior.watch_inputs(&[&input_pin]).unwrap();
+
+        simulation.set_input_pin(3, PinState::Low).unwrap();
+
+        let pin_event = pin_watcher.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        assert_eq!(pin_event.pin, 3);
+        assert_eq!(pin_event.pin_state, PinState::Low);
+        assert!(pin_watcher.take_error().is_none());
+    }
+
+    #[test]
+    fn pin_watcher_removes_listener_on_drop() {
+        let (iowarrior, _) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        let input_pin = iowarrior.setup_input(3).unwrap();
+
+        drop(iowarrior.watch_inputs(&[&input_pin]).unwrap());
+
+        assert!(iowarrior
+            .mut_data_mutex
+            .lock()
+            .unwrap()
+            .pin_listeners
+            .is_empty());
+    }
+}
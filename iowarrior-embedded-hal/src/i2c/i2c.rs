@@ -1,13 +1,15 @@
 use crate::i2c::{i2c_service, I2CConfig, I2CError};
-use crate::iowarrior::{peripheral_service, IOWarriorData, IOWarriorMutData, Peripheral};
-use std::cell::RefCell;
+use crate::iowarrior::{
+    iowarrior_service, peripheral_service, IOWarriorData, IOWarriorMutData, Peripheral,
+};
 use std::fmt;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct I2C {
-    pub(crate) data: Rc<IOWarriorData>,
-    pub(crate) mut_data_refcell: Rc<RefCell<IOWarriorMutData>>,
+    pub(crate) data: Arc<IOWarriorData>,
+    pub(crate) mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
     pub(crate) i2c_config: I2CConfig,
 }
 
@@ -22,7 +24,7 @@ impl Drop for I2C {
     fn drop(&mut self) {
         peripheral_service::disable_peripheral(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             Peripheral::I2C,
         );
     }
@@ -39,20 +41,24 @@ impl embedded_hal::i2c::I2c<embedded_hal::i2c::SevenBitAddress> for I2C {
         address: embedded_hal::i2c::SevenBitAddress,
         operations: &mut [embedded_hal::i2c::Operation],
     ) -> Result<(), Self::Error> {
-        let mut mut_data = self.mut_data_refcell.borrow_mut();
-
-        for operation in operations {
-            match operation {
-                embedded_hal::i2c::Operation::Read(buffer) => {
-                    i2c_service::read_data(&self.data, &mut mut_data, address, buffer)?;
-                }
-                embedded_hal::i2c::Operation::Write(buffer) => {
-                    i2c_service::write_data(&self.data, &mut mut_data, address, buffer)?;
-                }
-            }
-        }
-
-        Ok(())
+        i2c_service::transaction(
+            &self.data,
+            &mut self.mut_data_mutex.lock().unwrap(),
+            address,
+            operations,
+        )
+    }
+}
+
+#[cfg(feature = "embedded-hal-async")]
+impl embedded_hal_async::i2c::I2c<embedded_hal_async::i2c::SevenBitAddress> for I2C {
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: embedded_hal_async::i2c::SevenBitAddress,
+        operations: &mut [embedded_hal_async::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        i2c_service::transaction_async(&self.data, &self.mut_data_mutex, address, operations).await
     }
 }
 
@@ -64,7 +70,7 @@ impl embedded_hal_0::blocking::i2c::Write for I2C {
     fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
         i2c_service::write_data(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             address,
             bytes,
         )
@@ -79,7 +85,7 @@ impl embedded_hal_0::blocking::i2c::Read for I2C {
     fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
         i2c_service::read_data(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             address,
             buffer,
         )
@@ -97,7 +103,7 @@ impl embedded_hal_0::blocking::i2c::WriteRead for I2C {
         bytes: &[u8],
         buffer: &mut [u8],
     ) -> Result<(), Self::Error> {
-        let mut mut_data = self.mut_data_refcell.borrow_mut();
+        let mut mut_data = self.mut_data_mutex.lock().unwrap();
 
         i2c_service::write_data(&self.data, &mut mut_data, address, bytes)?;
         i2c_service::read_data(&self.data, &mut mut_data, address, buffer)
@@ -109,4 +115,18 @@ impl I2C {
     pub fn get_config(&self) -> I2CConfig {
         self.i2c_config
     }
+
+    #[inline]
+    pub fn read_with_timeout(
+        &mut self,
+        address: u8,
+        buffer: &mut [u8],
+        timeout: Duration,
+    ) -> Result<(), I2CError> {
+        let mut mut_data = self.mut_data_mutex.lock().unwrap();
+
+        iowarrior_service::with_read_timeout(&mut mut_data, timeout, |mut_data| {
+            i2c_service::read_data(&self.data, mut_data, address, buffer)
+        })
+    }
 }
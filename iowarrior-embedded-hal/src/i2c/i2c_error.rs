@@ -1,12 +1,18 @@
+use crate::communication::TransportError;
 use embedded_hal::i2c::NoAcknowledgeSource;
-use hidapi::HidError;
 use thiserror::Error;
 
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum I2CError {
-    #[error("USB HID error.")]
-    ErrorUSB(HidError),
+    #[error("USB transport error.")]
+    ErrorUSB(#[source] TransportError),
+    #[error("IOWarrior disconnected.")]
+    Disconnected,
+    #[error("Timeout while waiting for IOWarrior.")]
+    Timeout,
+    #[error("IOWarrior operation cancelled.")]
+    Cancelled,
     #[error("Invalid 7 bit I2C address.")]
     InvalidAddress,
     #[error("I2C slave does not acknowledge command byte.")]
@@ -23,10 +29,24 @@ pub enum I2CError {
     BusError,
 }
 
+impl From<TransportError> for I2CError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Disconnected => I2CError::Disconnected,
+            TransportError::Timeout => I2CError::Timeout,
+            TransportError::Cancelled => I2CError::Cancelled,
+            error => I2CError::ErrorUSB(error),
+        }
+    }
+}
+
 impl embedded_hal::i2c::Error for I2CError {
     fn kind(&self) -> embedded_hal::i2c::ErrorKind {
         match self {
             I2CError::ErrorUSB(_) => embedded_hal::i2c::ErrorKind::Other,
+            I2CError::Disconnected => embedded_hal::i2c::ErrorKind::Other,
+            I2CError::Timeout => embedded_hal::i2c::ErrorKind::Other,
+            I2CError::Cancelled => embedded_hal::i2c::ErrorKind::Other,
             I2CError::NoAcknowledge => {
                 embedded_hal::i2c::ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)
             }
@@ -1,40 +1,48 @@
 use crate::bits::Bit::{Bit0, Bit6, Bit7};
 use crate::bits::Bitmasking;
-use crate::communication::communication_service;
+#[cfg(feature = "embedded-hal-async")]
+use crate::communication::blocking_service;
+use crate::communication::TransportError;
 use crate::i2c::{I2CConfig, I2CError, I2C};
 use crate::iowarrior::{
-    peripheral_service, IOWarriorMutData, IOWarriorType, Peripheral, PeripheralSetupError, Pipe,
+    iowarrior_service, peripheral_service, IOWarriorMutData, IOWarriorType, Peripheral,
+    PeripheralSetupError, Pipe,
 };
 use crate::iowarrior::{IOWarriorData, Report, ReportId};
 use crate::pin;
-use hidapi::HidError;
-use std::cell::{RefCell, RefMut};
+use embedded_hal::i2c::Operation;
 use std::iter;
-use std::rc::Rc;
+#[cfg(feature = "embedded-hal-async")]
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 pub fn new(
-    data: &Rc<IOWarriorData>,
-    mut_data_refcell: &Rc<RefCell<IOWarriorMutData>>,
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
     i2c_config: I2CConfig,
 ) -> Result<I2C, PeripheralSetupError> {
-    let mut mut_data = mut_data_refcell.borrow_mut();
+    let mut mut_data = mut_data_mutex.lock().unwrap();
 
     let i2c_pins = get_i2c_pins(data.device_type);
 
     peripheral_service::precheck_peripheral(&data, &mut mut_data, Peripheral::I2C, &i2c_pins)?;
 
     send_enable_i2c(data, &mut mut_data, &i2c_config, &i2c_pins)
-        .map_err(|x| PeripheralSetupError::ErrorUSB(x))?;
+        .map_err(PeripheralSetupError::from)?;
 
     peripheral_service::post_enable(&mut mut_data, &i2c_pins, Peripheral::I2C);
 
     Ok(I2C {
         data: data.clone(),
-        mut_data_refcell: mut_data_refcell.clone(),
+        mut_data_mutex: mut_data_mutex.clone(),
         i2c_config,
     })
 }
 
+pub fn get_alternate_pins(device_type: IOWarriorType) -> Vec<u8> {
+    get_i2c_pins(device_type)
+}
+
 fn get_i2c_pins(device_type: IOWarriorType) -> Vec<u8> {
     match device_type {
         IOWarriorType::IOWarrior40 => vec![pin!(0, 6), pin!(0, 7)],
@@ -54,10 +62,10 @@ fn get_i2c_pins(device_type: IOWarriorType) -> Vec<u8> {
 
 fn send_enable_i2c(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     i2c_config: &I2CConfig,
     i2c_pins: &Vec<u8>,
-) -> Result<(), HidError> {
+) -> Result<(), TransportError> {
     let mut report = data.create_report(Pipe::I2CMode);
 
     report.buffer[0] = ReportId::I2cSetup.get_value();
@@ -78,12 +86,94 @@ fn send_enable_i2c(
         | IOWarriorType::IOWarrior28L => {}
     }
 
-    communication_service::write_report(&mut mut_data.communication_data, &mut report)
+    iowarrior_service::write_report(data, mut_data, &report)?;
+
+    peripheral_service::store_peripheral_setup(mut_data, Peripheral::I2C, vec![report]);
+
+    Ok(())
+}
+
+pub fn transaction(
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    address: u8,
+    operations: &mut [Operation],
+) -> Result<(), I2CError> {
+    for operation in operations {
+        match operation {
+            Operation::Read(buffer) => {
+                read_data(data, mut_data, address, buffer)?;
+            }
+            Operation::Write(buffer) => {
+                write_data(data, mut_data, address, buffer)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "embedded-hal-async")]
+pub async fn transaction_async(
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
+    address: u8,
+    operations: &mut [Operation<'_>],
+) -> Result<(), I2CError> {
+    let data = data.clone();
+    let mut_data_mutex = mut_data_mutex.clone();
+
+    let mut buffers: Vec<(bool, Vec<u8>)> = operations
+        .iter()
+        .map(|operation| match operation {
+            Operation::Read(buffer) => (true, buffer.to_vec()),
+            Operation::Write(buffer) => (false, buffer.to_vec()),
+        })
+        .collect();
+
+    let (result, buffers) = blocking_service::spawn_blocking(move |cancelled| {
+        let result = (|| {
+            let mut mut_data = mut_data_mutex.lock().unwrap();
+
+            for (read, buffer) in buffers.iter_mut() {
+                // A dropped future stops the transaction before the next operation.
+                if cancelled.load(Ordering::Relaxed) {
+                    return Err(I2CError::Cancelled);
+                }
+
+                let mut operation = match read {
+                    true => Operation::Read(buffer),
+                    false => Operation::Write(buffer),
+                };
+
+                transaction(
+                    &data,
+                    &mut mut_data,
+                    address,
+                    std::slice::from_mut(&mut operation),
+                )?;
+            }
+
+            Ok(())
+        })();
+
+        (result, buffers)
+    })
+    .map_err(I2CError::from)?
+    .await;
+
+    for (operation, (_, buffer)) in operations.iter_mut().zip(buffers) {
+        if let Operation::Read(read) = operation {
+            read.copy_from_slice(&buffer);
+        }
+    }
+
+    result
 }
 
 pub fn write_data(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     address: u8,
     buffer: &[u8],
 ) -> Result<(), I2CError> {
@@ -129,8 +219,7 @@ pub fn write_data(
             .buffer
             .extend(iter::repeat(0u8).take(data.special_report_size - report.buffer.len()));
 
-        communication_service::write_report(&mut mut_data.communication_data, &report)
-            .map_err(|x| I2CError::ErrorUSB(x))?;
+        iowarrior_service::write_report(data, mut_data, &report).map_err(I2CError::from)?;
     }
 
     _ = read_report(data, mut_data, report_id)?;
@@ -140,7 +229,7 @@ pub fn write_data(
 
 pub fn read_data(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     address: u8,
     buffer: &mut [u8],
 ) -> Result<(), I2CError> {
@@ -166,8 +255,7 @@ pub fn read_data(
                 value
             };
 
-            communication_service::write_report(&mut mut_data.communication_data, &report)
-                .map_err(|x| I2CError::ErrorUSB(x))?;
+            iowarrior_service::write_report(data, mut_data, &report).map_err(I2CError::from)?;
         }
 
         {
@@ -182,14 +270,11 @@ pub fn read_data(
 
 fn read_report(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     report_id: ReportId,
 ) -> Result<Report, I2CError> {
-    let report = communication_service::read_report(
-        &mut mut_data.communication_data,
-        data.create_report(Pipe::I2CMode),
-    )
-    .map_err(|x| I2CError::ErrorUSB(x))?;
+    let report = iowarrior_service::read_report(data, mut_data, data.create_report(Pipe::I2CMode))
+        .map_err(I2CError::from)?;
 
     assert_eq!(report.buffer[0], report_id.get_value());
 
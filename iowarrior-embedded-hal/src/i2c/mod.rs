@@ -6,3 +6,5 @@ pub(crate) mod i2c_service;
 pub use self::i2c::*;
 pub use self::i2c_config::*;
 pub use self::i2c_error::*;
+
+static_assertions::assert_impl_all!(I2C: Send, Sync);
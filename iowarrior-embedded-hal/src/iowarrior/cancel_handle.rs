@@ -0,0 +1,23 @@
+use crate::iowarrior::IOWarriorData;
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Clone, Debug)]
+pub struct CancelHandle {
+    pub(crate) data: Arc<IOWarriorData>,
+}
+
+impl fmt::Display for CancelHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl CancelHandle {
+    #[inline]
+    pub fn cancel(&self) {
+        let canceller = self.data.canceller_mutex.lock().unwrap().clone();
+
+        canceller.cancel();
+    }
+}
@@ -1,19 +1,21 @@
 use crate::adc::{adc_service, ADCConfig, ADC};
-use crate::digital::{digital_service, InputPin, OutputPin, PinSetupError};
+use crate::communication::{TransportError, USBInfo};
+use crate::digital::{digital_service, pin_watcher_service, InputPin, OutputPin, PinSetupError};
+use crate::digital::{DevicePin, PinBatch, PinGroup, PinWatcher, PinWatcherConfig};
 use crate::i2c::{i2c_service, I2CConfig, I2C};
-use crate::iowarrior::{IOWarriorData, IOWarriorMutData};
+use crate::iowarrior::{iowarrior_service, CancelHandle, IOWarriorData, IOWarriorMutData};
 use crate::iowarrior::{IOWarriorType, PeripheralSetupError};
 use crate::pwm::{pwm_service, PWMConfig, PWM};
 use crate::spi::{spi_service, SPIConfig, SPI};
 use embedded_hal::digital::PinState;
-use std::cell::RefCell;
 use std::fmt;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct IOWarrior {
-    pub(crate) data: Rc<IOWarriorData>,
-    pub(crate) mut_data_refcell: Rc<RefCell<IOWarriorMutData>>,
+    pub(crate) data: Arc<IOWarriorData>,
+    pub(crate) mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
 }
 
 impl fmt::Display for IOWarrior {
@@ -38,19 +40,57 @@ impl IOWarrior {
         self.data.device_serial.clone()
     }
 
+    #[inline]
+    pub fn get_usb_info(&self) -> USBInfo {
+        self.data.usb_info.clone()
+    }
+
+    #[inline]
+    pub fn get_read_timeout(&self) -> Option<Duration> {
+        self.mut_data_mutex.lock().unwrap().read_timeout
+    }
+
+    #[inline]
+    pub fn get_write_timeout(&self) -> Option<Duration> {
+        self.mut_data_mutex.lock().unwrap().write_timeout
+    }
+
+    #[inline]
+    pub fn set_read_timeout(&self, read_timeout: Option<Duration>) -> Result<(), TransportError> {
+        let mut mut_data = self.mut_data_mutex.lock().unwrap();
+        let write_timeout = mut_data.write_timeout;
+
+        iowarrior_service::set_timeouts(&mut mut_data, read_timeout, write_timeout)
+    }
+
+    #[inline]
+    pub fn set_write_timeout(&self, write_timeout: Option<Duration>) -> Result<(), TransportError> {
+        let mut mut_data = self.mut_data_mutex.lock().unwrap();
+        let read_timeout = mut_data.read_timeout;
+
+        iowarrior_service::set_timeouts(&mut mut_data, read_timeout, write_timeout)
+    }
+
+    #[inline]
+    pub fn get_cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            data: self.data.clone(),
+        }
+    }
+
     #[inline]
     pub fn setup_i2c_with_config(
         &self,
         i2c_config: I2CConfig,
     ) -> Result<I2C, PeripheralSetupError> {
-        i2c_service::new(&self.data, &self.mut_data_refcell, i2c_config)
+        i2c_service::new(&self.data, &self.mut_data_mutex, i2c_config)
     }
 
     #[inline]
     pub fn setup_i2c(&self) -> Result<I2C, PeripheralSetupError> {
         let i2c_config = I2CConfig::default();
 
-        i2c_service::new(&self.data, &self.mut_data_refcell, i2c_config)
+        i2c_service::new(&self.data, &self.mut_data_mutex, i2c_config)
     }
 
     #[inline]
@@ -58,14 +98,14 @@ impl IOWarrior {
         &self,
         pwm_config: PWMConfig,
     ) -> Result<Vec<PWM>, PeripheralSetupError> {
-        pwm_service::new(&self.data, &self.mut_data_refcell, pwm_config)
+        pwm_service::new(&self.data, &self.mut_data_mutex, pwm_config)
     }
 
     #[inline]
     pub fn setup_pwm(&self) -> Result<Vec<PWM>, PeripheralSetupError> {
         let pwm_config = PWMConfig::default();
 
-        pwm_service::new(&self.data, &self.mut_data_refcell, pwm_config)
+        pwm_service::new(&self.data, &self.mut_data_mutex, pwm_config)
     }
 
     #[inline]
@@ -73,14 +113,14 @@ impl IOWarrior {
         &self,
         adc_config: ADCConfig,
     ) -> Result<ADC, PeripheralSetupError> {
-        adc_service::new(&self.data, &self.mut_data_refcell, adc_config)
+        adc_service::new(&self.data, &self.mut_data_mutex, adc_config)
     }
 
     #[inline]
     pub fn setup_adc(&self) -> Result<ADC, PeripheralSetupError> {
         let adc_config = ADCConfig::default();
 
-        adc_service::new(&self.data, &self.mut_data_refcell, adc_config)
+        adc_service::new(&self.data, &self.mut_data_mutex, adc_config)
     }
 
     #[inline]
@@ -88,28 +128,78 @@ impl IOWarrior {
         &self,
         spi_config: SPIConfig,
     ) -> Result<SPI, PeripheralSetupError> {
-        spi_service::new(&self.data, &self.mut_data_refcell, spi_config)
+        spi_service::new(&self.data, &self.mut_data_mutex, spi_config)
     }
 
     #[inline]
     pub fn setup_spi(&self) -> Result<SPI, PeripheralSetupError> {
         let spi_config = SPIConfig::default();
 
-        spi_service::new(&self.data, &self.mut_data_refcell, spi_config)
+        spi_service::new(&self.data, &self.mut_data_mutex, spi_config)
+    }
+
+    #[inline]
+    pub fn setup_output_as_high(&self, pin: impl DevicePin) -> Result<OutputPin, PinSetupError> {
+        let pin = digital_service::get_device_pin_value(self.data.device_type, pin)?;
+
+        digital_service::new_output(&self.data, &self.mut_data_mutex, PinState::High, pin)
+    }
+
+    #[inline]
+    pub fn setup_output_as_low(&self, pin: impl DevicePin) -> Result<OutputPin, PinSetupError> {
+        let pin = digital_service::get_device_pin_value(self.data.device_type, pin)?;
+
+        digital_service::new_output(&self.data, &self.mut_data_mutex, PinState::Low, pin)
     }
 
     #[inline]
-    pub fn setup_output_as_high(&self, pin: u8) -> Result<OutputPin, PinSetupError> {
-        digital_service::new_output(&self.data, &self.mut_data_refcell, PinState::High, pin)
+    pub fn setup_input(&self, pin: impl DevicePin) -> Result<InputPin, PinSetupError> {
+        let pin = digital_service::get_device_pin_value(self.data.device_type, pin)?;
+
+        digital_service::new_input(&self.data, &self.mut_data_mutex, pin)
+    }
+
+    #[inline]
+    pub fn setup_pin_group(
+        &self,
+        pins: &[impl DevicePin + Copy],
+    ) -> Result<PinGroup, PinSetupError> {
+        let pins = pins
+            .iter()
+            .map(|pin| digital_service::get_device_pin_value(self.data.device_type, *pin))
+            .collect::<Result<Vec<u8>, PinSetupError>>()?;
+
+        digital_service::new_pin_group(&self.data, &self.mut_data_mutex, &pins)
     }
 
     #[inline]
-    pub fn setup_output_as_low(&self, pin: u8) -> Result<OutputPin, PinSetupError> {
-        digital_service::new_output(&self.data, &self.mut_data_refcell, PinState::Low, pin)
+    pub fn start_pin_batch(&self) -> PinBatch {
+        digital_service::new_pin_batch(&self.data, &self.mut_data_mutex)
+    }
+
+    #[inline]
+    pub fn watch_inputs_with_config(
+        &self,
+        input_pins: &[&InputPin],
+        pin_watcher_config: PinWatcherConfig,
+    ) -> Result<PinWatcher, PinSetupError> {
+        pin_watcher_service::create_pin_watcher(
+            &self.data,
+            &self.mut_data_mutex,
+            input_pins,
+            pin_watcher_config,
+        )
     }
 
     #[inline]
-    pub fn setup_input(&self, pin: u8) -> Result<InputPin, PinSetupError> {
-        digital_service::new_input(&self.data, &self.mut_data_refcell, pin)
+    pub fn watch_inputs(&self, input_pins: &[&InputPin]) -> Result<PinWatcher, PinSetupError> {
+        let pin_watcher_config = PinWatcherConfig::default();
+
+        pin_watcher_service::create_pin_watcher(
+            &self.data,
+            &self.mut_data_mutex,
+            input_pins,
+            pin_watcher_config,
+        )
     }
 }
@@ -1,5 +1,7 @@
+use crate::communication::{Canceller, USBInfo};
 use crate::iowarrior::{IOWarriorType, Pipe, Report};
 use std::fmt;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug)]
 pub struct IOWarriorData {
@@ -8,6 +10,8 @@ pub struct IOWarriorData {
     pub device_type: IOWarriorType,
     pub standard_report_size: usize,
     pub special_report_size: usize,
+    pub canceller_mutex: Mutex<Arc<dyn Canceller>>,
+    pub usb_info: USBInfo,
 }
 
 impl IOWarriorData {
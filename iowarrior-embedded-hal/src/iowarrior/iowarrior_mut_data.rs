@@ -1,15 +1,25 @@
-use crate::communication::CommunicationData;
-use crate::iowarrior::Peripheral;
+use crate::communication::{InitializationConfig, Transport};
+use crate::digital::PinListener;
+use crate::iowarrior::{Peripheral, PeripheralSetup};
 use crate::iowarrior::{Report, UsedPin};
 use std::fmt;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct IOWarriorMutData {
-    pub communication_data: CommunicationData,
+    pub communication_data: Box<dyn Transport>,
     pub pins_in_use: Vec<UsedPin>,
     pub dangling_peripherals: Vec<Peripheral>,
     pub pins_write_report: Report,
+    pub pins_write_batches: usize,
+    pub pins_write_rollback: Option<Report>,
     pub pins_read_report: Report,
+    pub pin_listeners: Vec<PinListener>,
+    pub peripheral_setups: Vec<PeripheralSetup>,
+    pub disconnected: bool,
+    pub reconnect_config: Option<InitializationConfig>,
+    pub read_timeout: Option<Duration>,
+    pub write_timeout: Option<Duration>,
 }
 
 impl fmt::Display for IOWarriorMutData {
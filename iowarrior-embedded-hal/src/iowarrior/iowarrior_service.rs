@@ -1,50 +1,288 @@
-use crate::communication::{communication_service, CommunicationData};
+use crate::communication::initialization_service;
+use crate::communication::recording::recording_service;
+use crate::communication::trace::TraceHeader;
+use crate::communication::{
+    DisconnectedTransport, InitializationConfig, InitializationError, Transport, TransportError,
+};
 use crate::iowarrior::{
     IOWarrior, IOWarriorData, IOWarriorMutData, IOWarriorType, Pipe, Report, ReportId,
 };
-use hidapi::HidError;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+pub const NON_LEGACY_REVISION: u16 = 0x1010;
 
 pub fn create_iowarrior(
     device_type: IOWarriorType,
     device_revision: u16,
     device_serial: String,
-    mut communication_data: CommunicationData,
-) -> Result<IOWarrior, HidError> {
+    communication_data: Box<dyn Transport>,
+    config: &InitializationConfig,
+) -> Result<IOWarrior, InitializationError> {
+    // Reconnecting finds the device again by its serial number, which legacy IOWarrior40s lack.
+    if config.reconnect && device_serial.is_empty() {
+        return Err(InitializationError::ReconnectNotSupported { device_type });
+    }
+
+    let mut communication_data = match &config.trace_directory {
+        None => communication_data,
+        Some(trace_directory) => recording_service::start_recording(
+            communication_data,
+            TraceHeader {
+                device_type,
+                device_revision,
+                device_serial: device_serial.clone(),
+            },
+            trace_directory,
+        )
+        .map_err(InitializationError::ErrorUSB)?,
+    };
+
     let mut data = IOWarriorData {
         device_serial,
         device_revision,
         device_type,
         standard_report_size: get_standard_report_size(device_type),
         special_report_size: get_special_report_size(device_type),
+        canceller_mutex: Mutex::new(communication_data.get_canceller()),
+        usb_info: communication_data.get_usb_info(),
     };
 
+    check_report_sizes(&mut data).map_err(InitializationError::ErrorUSB)?;
+
     if data.device_type == IOWarriorType::IOWarrior56 {
-        data.device_type = get_iowarrior56_subtype(&data, &mut communication_data)?;
+        data.device_type = get_iowarrior56_subtype(&data, communication_data.as_mut())
+            .map_err(InitializationError::ErrorUSB)?;
     }
 
     if data.device_type == IOWarriorType::IOWarrior28 {
-        data.device_type = get_iowarrior28_subtype(&data, &mut communication_data)?;
+        data.device_type = get_iowarrior28_subtype(&data, communication_data.as_mut())
+            .map_err(InitializationError::ErrorUSB)?;
     }
 
-    let pins_report = get_pins_report(&data, &mut communication_data)?;
+    communication_data
+        .set_timeouts(config.read_timeout, config.write_timeout)
+        .map_err(InitializationError::ErrorUSB)?;
+
+    let pins_report = match is_legacy_iowarrior40(&data) {
+        true => get_default_pins_report(&data),
+        false => get_pins_report(&data, communication_data.as_mut())
+            .map_err(InitializationError::ErrorUSB)?,
+    };
 
     let mut_data = IOWarriorMutData {
         pins_in_use: vec![],
         dangling_peripherals: vec![],
         pins_write_report: pins_report.clone(),
+        pins_write_batches: 0,
+        pins_write_rollback: None,
         pins_read_report: pins_report,
+        pin_listeners: vec![],
+        peripheral_setups: vec![],
+        disconnected: false,
+        reconnect_config: match config.reconnect {
+            true => Some(InitializationConfig {
+                trace_directory: None,
+                ..config.clone()
+            }),
+            false => None,
+        },
+        read_timeout: config.read_timeout,
+        write_timeout: config.write_timeout,
         communication_data,
     };
 
     Ok(IOWarrior {
-        data: Rc::new(data),
-        mut_data_refcell: Rc::new(RefCell::new(mut_data)),
+        data: Arc::new(data),
+        mut_data_mutex: Arc::new(Mutex::new(mut_data)),
     })
 }
 
-fn get_standard_report_size(device_type: IOWarriorType) -> usize {
+pub fn set_timeouts(
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+) -> Result<(), TransportError> {
+    mut_data.read_timeout = read_timeout;
+    mut_data.write_timeout = write_timeout;
+
+    mut_data
+        .communication_data
+        .set_timeouts(read_timeout, write_timeout)
+}
+
+// Overrides the read timeout for one operation, each report read within it uses the timeout.
+pub fn with_read_timeout<T, E>(
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    timeout: Duration,
+    function: impl FnOnce(&mut MutexGuard<IOWarriorMutData>) -> Result<T, E>,
+) -> Result<T, E>
+where
+    E: From<TransportError>,
+{
+    let (read_timeout, write_timeout) = (mut_data.read_timeout, mut_data.write_timeout);
+
+    if let Err(error) = set_timeouts(mut_data, Some(timeout), write_timeout) {
+        mut_data.read_timeout = read_timeout;
+
+        return Err(E::from(error));
+    }
+
+    let result = function(mut_data);
+    let restore_result = set_timeouts(mut_data, read_timeout, write_timeout);
+
+    // An error of the operation takes priority over an error restoring the timeouts.
+    let value = result?;
+    restore_result?;
+
+    Ok(value)
+}
+
+pub fn write_report(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    report: &Report,
+) -> Result<(), TransportError> {
+    check_connection(data, mut_data)?;
+
+    let result = mut_data.communication_data.write_report(report);
+
+    check_result(data, mut_data, result)
+}
+
+pub fn read_report(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    report: Report,
+) -> Result<Report, TransportError> {
+    check_connection(data, mut_data)?;
+
+    let result = mut_data.communication_data.read_report(report);
+
+    check_result(data, mut_data, result)
+}
+
+pub fn read_report_non_blocking(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    report: Report,
+) -> Result<Option<Report>, TransportError> {
+    check_connection(data, mut_data)?;
+
+    let result = mut_data.communication_data.read_report_non_blocking(report);
+
+    check_result(data, mut_data, result)
+}
+
+fn check_connection(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<(), TransportError> {
+    match mut_data.disconnected {
+        true => reconnect(data, mut_data),
+        false => Ok(()),
+    }
+}
+
+fn check_result<T>(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    result: Result<T, TransportError>,
+) -> Result<T, TransportError> {
+    if let Err(error) = &result {
+        if matches!(error, TransportError::Disconnected) && !mut_data.disconnected {
+            mut_data.disconnected = true;
+            release_transport(data, mut_data);
+        }
+    }
+
+    result
+}
+
+// Frees the handles of the lost device, so reconnecting can enumerate the devices afresh.
+fn release_transport(data: &IOWarriorData, mut_data: &mut MutexGuard<IOWarriorMutData>) {
+    let disconnected_transport = Box::new(DisconnectedTransport {
+        usb_info: data.usb_info.clone(),
+    });
+
+    if let Some(communication_data) = mut_data
+        .communication_data
+        .replace_transport(disconnected_transport)
+    {
+        mut_data.communication_data = communication_data;
+    }
+
+    *data.canceller_mutex.lock().unwrap() = mut_data.communication_data.get_canceller();
+}
+
+fn reconnect(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<(), TransportError> {
+    let config = match &mut_data.reconnect_config {
+        None => return Err(TransportError::Disconnected),
+        Some(config) => config.clone(),
+    };
+
+    let iowarrior = initialization_service::get_iowarrior_with_config(&data.device_serial, config)
+        .map_err(|_| TransportError::Disconnected)?;
+
+    if iowarrior.data.device_type != data.device_type {
+        return Err(TransportError::Disconnected);
+    }
+
+    let new_mut_data = Arc::try_unwrap(iowarrior.mut_data_mutex)
+        .map_err(|_| TransportError::Disconnected)?
+        .into_inner()
+        .map_err(|_| TransportError::Disconnected)?;
+
+    // A recording transport keeps its trace file and records the new transport from here on.
+    if let Some(communication_data) = mut_data
+        .communication_data
+        .replace_transport(new_mut_data.communication_data)
+    {
+        mut_data.communication_data = communication_data;
+    }
+    mut_data.pins_read_report = new_mut_data.pins_read_report;
+
+    *data.canceller_mutex.lock().unwrap() = mut_data.communication_data.get_canceller();
+
+    let (read_timeout, write_timeout) = (mut_data.read_timeout, mut_data.write_timeout);
+
+    mut_data
+        .communication_data
+        .set_timeouts(read_timeout, write_timeout)?;
+
+    for peripheral in std::mem::take(&mut mut_data.dangling_peripherals) {
+        mut_data
+            .pins_in_use
+            .retain(|x| x.peripheral != Some(peripheral));
+    }
+
+    let pins_write_report = mut_data.pins_write_report.clone();
+
+    mut_data
+        .communication_data
+        .write_report(&pins_write_report)?;
+
+    let IOWarriorMutData {
+        communication_data,
+        peripheral_setups,
+        ..
+    } = &mut **mut_data;
+
+    for peripheral_setup in peripheral_setups.iter() {
+        for report in &peripheral_setup.reports {
+            communication_data.write_report(report)?;
+        }
+    }
+
+    mut_data.disconnected = false;
+
+    Ok(())
+}
+
+pub fn get_standard_report_size(device_type: IOWarriorType) -> usize {
     match device_type {
         IOWarriorType::IOWarrior24 | IOWarriorType::IOWarrior24PowerVampire => 3,
         IOWarriorType::IOWarrior28
@@ -57,7 +295,7 @@ fn get_standard_report_size(device_type: IOWarriorType) -> usize {
     }
 }
 
-fn get_special_report_size(device_type: IOWarriorType) -> usize {
+pub fn get_special_report_size(device_type: IOWarriorType) -> usize {
     match device_type {
         IOWarriorType::IOWarrior40
         | IOWarriorType::IOWarrior24
@@ -71,21 +309,40 @@ fn get_special_report_size(device_type: IOWarriorType) -> usize {
     }
 }
 
+fn check_report_sizes(data: &mut IOWarriorData) -> Result<(), TransportError> {
+    if let Some(packet_size) = data.usb_info.packet_sizes.first() {
+        if (*packet_size as usize) < data.standard_report_size - 1 {
+            return Err(TransportError::Other(format!(
+                "Unexpected packet size {} on IOPins pipe.",
+                packet_size
+            )));
+        }
+    }
+
+    if let Some(packet_size) = data.usb_info.packet_sizes.get(1) {
+        if *packet_size > 0 {
+            data.special_report_size = *packet_size as usize;
+        }
+    }
+
+    Ok(())
+}
+
 fn get_iowarrior56_subtype(
     data: &IOWarriorData,
-    communication_data: &mut CommunicationData,
-) -> Result<IOWarriorType, HidError> {
+    communication_data: &mut dyn Transport,
+) -> Result<IOWarriorType, TransportError> {
     let mut report = data.create_report(Pipe::SpecialMode);
 
     report.buffer[0] = ReportId::AdcSetup.get_value();
     report.buffer[1] = 0x00;
 
-    match communication_service::write_report(communication_data, &report) {
+    match communication_data.write_report(&report) {
         Ok(_) => Ok(IOWarriorType::IOWarrior56),
         Err(error) => {
             match error {
-                HidError::IncompleteSendError { sent, all } => {
-                    if sent == 0 {
+                TransportError::ShortWrite { written, expected } => {
+                    if written == 0 {
                         return Ok(IOWarriorType::IOWarrior56Dongle);
                     }
                 }
@@ -99,19 +356,19 @@ fn get_iowarrior56_subtype(
 
 fn get_iowarrior28_subtype(
     data: &IOWarriorData,
-    communication_data: &mut CommunicationData,
-) -> Result<IOWarriorType, HidError> {
+    communication_data: &mut dyn Transport,
+) -> Result<IOWarriorType, TransportError> {
     let mut report = data.create_report(Pipe::ADCMode);
 
     report.buffer[0] = ReportId::AdcSetup.get_value();
     report.buffer[1] = 0x00;
 
-    match communication_service::write_report(communication_data, &mut report) {
+    match communication_data.write_report(&report) {
         Ok(_) => Ok(IOWarriorType::IOWarrior28),
         Err(error) => {
             match error {
-                HidError::IncompleteSendError { sent, all } => {
-                    if sent == 0 {
+                TransportError::ShortWrite { written, expected } => {
+                    if written == 0 {
                         return Ok(IOWarriorType::IOWarrior28Dongle);
                     }
                 }
@@ -123,23 +380,33 @@ fn get_iowarrior28_subtype(
     }
 }
 
+pub fn is_legacy_iowarrior40(data: &IOWarriorData) -> bool {
+    data.device_type == IOWarriorType::IOWarrior40 && data.device_revision < NON_LEGACY_REVISION
+}
+
+fn get_default_pins_report(data: &IOWarriorData) -> Report {
+    let mut report = data.create_report(Pipe::IOPins);
+
+    report.buffer[0] = ReportId::GpioReadWrite.get_value();
+    report.buffer[1..].fill(0xFF);
+
+    report
+}
+
 fn get_pins_report(
     data: &IOWarriorData,
-    communication_data: &mut CommunicationData,
-) -> Result<Report, HidError> {
+    communication_data: &mut dyn Transport,
+) -> Result<Report, TransportError> {
     {
         let mut report = data.create_report(Pipe::SpecialMode);
 
         report.buffer[0] = ReportId::GpioSpecialRead.get_value();
 
-        communication_service::write_report(communication_data, &report)?;
+        communication_data.write_report(&report)?;
     }
 
     {
-        let mut report = communication_service::read_report(
-            communication_data,
-            data.create_report(Pipe::SpecialMode),
-        )?;
+        let mut report = communication_data.read_report(data.create_report(Pipe::SpecialMode))?;
 
         report.buffer[0] = ReportId::GpioReadWrite.get_value();
 
@@ -154,3 +421,72 @@ fn get_pins_report(
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::communication::sim::initialization_service::get_simulated_iowarrior;
+    use crate::communication::sim::initialization_service::get_simulated_iowarrior_with_config;
+    use crate::communication::sim::initialization_service::get_test_iowarrior;
+    use crate::communication::{InitializationConfig, InitializationError, TransportError};
+    use crate::iowarrior::{IOWarriorType, PeripheralSetupError};
+    use std::time::Duration;
+
+    #[test]
+    fn reconnect_requires_serial_number() {
+        let config = InitializationConfig {
+            reconnect: true,
+            ..InitializationConfig::default()
+        };
+
+        let result =
+            get_simulated_iowarrior_with_config(IOWarriorType::IOWarrior40, 0x1000, "", &config);
+
+        assert!(matches!(
+            result,
+            Err(InitializationError::ReconnectNotSupported {
+                device_type: IOWarriorType::IOWarrior40
+            })
+        ));
+    }
+
+    #[test]
+    fn legacy_iowarrior40_has_no_special_mode() {
+        let (iowarrior, _) =
+            get_simulated_iowarrior(IOWarriorType::IOWarrior40, 0x1000, "").unwrap();
+
+        assert!(matches!(
+            iowarrior.setup_i2c(),
+            Err(PeripheralSetupError::NotSupported)
+        ));
+        assert!(iowarrior.setup_input(crate::pin!(0, 0)).is_ok());
+    }
+
+    #[test]
+    fn iowarrior40_has_special_mode() {
+        let (iowarrior, _) =
+            get_simulated_iowarrior(IOWarriorType::IOWarrior40, 0x1010, "00000001").unwrap();
+
+        assert!(iowarrior.setup_i2c().is_ok());
+    }
+
+    #[test]
+    fn read_timeout_is_restored_after_operation() {
+        let (iowarrior, _) = get_test_iowarrior(IOWarriorType::IOWarrior24);
+
+        iowarrior
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+
+        let result: Result<(), TransportError> = super::with_read_timeout(
+            &mut iowarrior.mut_data_mutex.lock().unwrap(),
+            Duration::from_millis(1),
+            |mut_data| {
+                assert_eq!(mut_data.read_timeout, Some(Duration::from_millis(1)));
+                Err(TransportError::Timeout)
+            },
+        );
+
+        assert!(matches!(result, Err(TransportError::Timeout)));
+        assert_eq!(iowarrior.get_read_timeout(), Some(Duration::from_secs(1)));
+    }
+}
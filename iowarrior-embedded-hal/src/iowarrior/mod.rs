@@ -1,3 +1,4 @@
+mod cancel_handle;
 mod iowarrior;
 mod iowarrior_data;
 mod iowarrior_mut_data;
@@ -5,19 +6,24 @@ pub(crate) mod iowarrior_service;
 mod iowarrior_type;
 mod peripheral;
 pub(crate) mod peripheral_service;
+mod peripheral_setup;
 mod peripheral_setup_error;
 mod pipe;
 mod report;
 mod report_id;
 mod used_pin;
 
+pub use self::cancel_handle::*;
 pub use self::iowarrior::*;
 pub(crate) use self::iowarrior_data::*;
 pub(crate) use self::iowarrior_mut_data::*;
 pub use self::iowarrior_type::*;
 pub use self::peripheral::*;
+pub(crate) use self::peripheral_setup::*;
 pub use self::peripheral_setup_error::*;
-pub(crate) use self::pipe::*;
-pub(crate) use self::report::*;
+pub use self::pipe::*;
+pub use self::report::*;
 pub(crate) use self::report_id::*;
 pub(crate) use self::used_pin::*;
+
+static_assertions::assert_impl_all!(IOWarrior: Send, Sync);
@@ -1,15 +1,15 @@
 use crate::bits::Bit;
 use crate::bits::Bitmasking;
-use crate::communication::{communication_service, CommunicationData};
+use crate::communication::TransportError;
 use crate::iowarrior::{
-    IOWarriorData, IOWarriorMutData, Peripheral, PeripheralSetupError, Pipe, ReportId, UsedPin,
+    iowarrior_service, IOWarriorData, IOWarriorMutData, Peripheral, PeripheralSetup,
+    PeripheralSetupError, Pipe, Report, ReportId, UsedPin,
 };
 use embedded_hal::digital::PinState;
-use hidapi::HidError;
-use std::cell::RefMut;
+use std::sync::MutexGuard;
 
 pub fn get_used_pins(
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     peripheral: Peripheral,
 ) -> Vec<UsedPin> {
     mut_data
@@ -22,10 +22,15 @@ pub fn get_used_pins(
 
 pub fn precheck_peripheral(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     peripheral: Peripheral,
     required_pins: &Vec<u8>,
 ) -> Result<(), PeripheralSetupError> {
+    // Legacy IOWarrior40 firmware only supports the IO pins, not the special mode functions.
+    if iowarrior_service::is_legacy_iowarrior40(data) {
+        return Err(PeripheralSetupError::NotSupported);
+    }
+
     match mut_data
         .pins_in_use
         .iter()
@@ -36,7 +41,7 @@ pub fn precheck_peripheral(
         Some(_) => return Err(PeripheralSetupError::AlreadySetup),
     }
 
-    cleanup_dangling_modules(&data, mut_data).map_err(|x| PeripheralSetupError::ErrorUSB(x))?;
+    cleanup_dangling_modules(&data, mut_data).map_err(PeripheralSetupError::from)?;
 
     let pin_conflicts: Vec<_> = mut_data
         .pins_in_use
@@ -53,7 +58,7 @@ pub fn precheck_peripheral(
 }
 
 pub fn post_enable(
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     peripheral_pins: &Vec<u8>,
     peripheral: Peripheral,
 ) {
@@ -65,17 +70,32 @@ pub fn post_enable(
         }));
 }
 
+pub fn store_peripheral_setup(
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    peripheral: Peripheral,
+    reports: Vec<Report>,
+) {
+    mut_data
+        .peripheral_setups
+        .retain(|x| x.peripheral != peripheral);
+
+    mut_data.peripheral_setups.push(PeripheralSetup {
+        peripheral,
+        reports,
+    });
+}
+
 pub fn cleanup_dangling_modules(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
-) -> Result<(), HidError> {
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<(), TransportError> {
     if !mut_data.dangling_peripherals.is_empty() {
         for x in mut_data.dangling_peripherals.to_vec() {
             match x {
-                Peripheral::I2C => send_disable_i2c(&data, &mut mut_data.communication_data),
-                Peripheral::PWM => send_disable_pwm(&data, &mut mut_data.communication_data),
-                Peripheral::SPI => send_disable_spi(&data, &mut mut_data.communication_data),
-                Peripheral::ADC => send_disable_adc(&data, &mut mut_data.communication_data),
+                Peripheral::I2C => send_disable_i2c(data, mut_data),
+                Peripheral::PWM => send_disable_pwm(data, mut_data),
+                Peripheral::SPI => send_disable_spi(data, mut_data),
+                Peripheral::ADC => send_disable_adc(data, mut_data),
             }?;
 
             mut_data.dangling_peripherals.retain(|y| *y != x);
@@ -87,46 +107,105 @@ pub fn cleanup_dangling_modules(
 
 pub fn set_pin_output(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     pin_state: PinState,
     pin: u8,
-) -> Result<(), HidError> {
-    let byte_index = ((pin as usize) / 8usize) + 1;
-    let bit_index = Bit::from_u8(pin % 8u8);
+) -> Result<(), TransportError> {
+    set_pins_output(data, mut_data, &[(pin, pin_state)])
+}
 
+pub fn set_pins_output(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    pin_states: &[(u8, PinState)],
+) -> Result<(), TransportError> {
     let mut pins_write_report = mut_data.pins_write_report.clone();
 
-    pins_write_report.buffer[byte_index].set_bit(bit_index, bool::from(pin_state));
+    for (pin, pin_state) in pin_states {
+        let byte_index = ((*pin as usize) / 8usize) + 1;
+        let bit_index = Bit::from_u8(pin % 8u8);
 
-    match communication_service::write_report(&mut mut_data.communication_data, &pins_write_report)
-    {
+        pins_write_report.buffer[byte_index].set_bit(bit_index, bool::from(*pin_state));
+    }
+
+    if mut_data.pins_write_batches > 0 {
+        // Keep the last written report, so a failed flush can be rolled back.
+        if mut_data.pins_write_rollback.is_none() {
+            mut_data.pins_write_rollback = Some(mut_data.pins_write_report.clone());
+        }
+
+        mut_data.pins_write_report = pins_write_report;
+        return Ok(());
+    }
+
+    match iowarrior_service::write_report(data, mut_data, &pins_write_report) {
         Ok(_) => {
             mut_data.pins_write_report = pins_write_report;
+            mut_data.pins_write_rollback = None;
             Ok(())
         }
         Err(error) => Err(error),
     }
 }
 
-pub fn disable_gpio(data: &IOWarriorData, mut_data: &mut RefMut<IOWarriorMutData>, pin: u8) {
-    match set_pin_output(&data, mut_data, PinState::High, pin) {
+pub fn flush_pins_output(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<(), TransportError> {
+    if mut_data.pins_write_rollback.is_none() {
+        return Ok(());
+    }
+
+    let pins_write_report = mut_data.pins_write_report.clone();
+
+    iowarrior_service::write_report(data, mut_data, &pins_write_report)?;
+
+    mut_data.pins_write_rollback = None;
+
+    Ok(())
+}
+
+pub fn rollback_pins_output(mut_data: &mut MutexGuard<IOWarriorMutData>) {
+    if let Some(pins_write_report) = mut_data.pins_write_rollback.take() {
+        mut_data.pins_write_report = pins_write_report;
+    }
+}
+
+pub fn disable_gpio(data: &IOWarriorData, mut_data: &mut MutexGuard<IOWarriorMutData>, pin: u8) {
+    disable_gpios(data, mut_data, &[pin]);
+}
+
+pub fn disable_gpios(
+    data: &IOWarriorData,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    pins: &[u8],
+) {
+    let pin_states: Vec<_> = pins.iter().map(|pin| (*pin, PinState::High)).collect();
+
+    match set_pins_output(&data, mut_data, &pin_states) {
         Ok(_) => {}
         Err(_) => { /* Ignore error. Every following pin and peripheral can handle this. */ }
     };
 
-    mut_data.pins_in_use.retain(|x| x.pin == pin);
+    mut_data
+        .pins_in_use
+        .retain(|x| x.peripheral.is_some() || !pins.contains(&x.pin));
 }
 
 pub fn disable_peripheral(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     peripheral: Peripheral,
 ) {
+    mut_data
+        .peripheral_setups
+        .retain(|x| x.peripheral != peripheral);
+
     match match peripheral {
-        Peripheral::I2C => send_disable_i2c(data, &mut mut_data.communication_data),
-        Peripheral::PWM => send_disable_pwm(data, &mut mut_data.communication_data),
-        Peripheral::SPI => send_disable_spi(data, &mut mut_data.communication_data),
-        Peripheral::ADC => send_disable_adc(data, &mut mut_data.communication_data),
+        Peripheral::I2C => send_disable_i2c(data, mut_data),
+        Peripheral::PWM => send_disable_pwm(data, mut_data),
+        Peripheral::SPI => send_disable_spi(data, mut_data),
+        Peripheral::ADC => send_disable_adc(data, mut_data),
     } {
         Ok(_) => {
             mut_data
@@ -141,48 +220,48 @@ pub fn disable_peripheral(
 
 fn send_disable_i2c(
     data: &IOWarriorData,
-    communication_data: &mut CommunicationData,
-) -> Result<(), HidError> {
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<(), TransportError> {
     let mut report = data.create_report(Pipe::I2CMode);
 
     report.buffer[0] = ReportId::I2cSetup.get_value();
     report.buffer[1] = 0x00;
 
-    communication_service::write_report(communication_data, &report)
+    iowarrior_service::write_report(data, mut_data, &report)
 }
 
 fn send_disable_pwm(
     data: &IOWarriorData,
-    communication_data: &mut CommunicationData,
-) -> Result<(), HidError> {
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<(), TransportError> {
     let mut report = data.create_report(Pipe::SpecialMode);
 
     report.buffer[0] = ReportId::PwmSetup.get_value();
     report.buffer[1] = 0x00;
 
-    communication_service::write_report(communication_data, &report)
+    iowarrior_service::write_report(data, mut_data, &report)
 }
 
 fn send_disable_spi(
     data: &IOWarriorData,
-    communication_data: &mut CommunicationData,
-) -> Result<(), HidError> {
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<(), TransportError> {
     let mut report = data.create_report(Pipe::SpecialMode);
 
     report.buffer[0] = ReportId::SpiSetup.get_value();
     report.buffer[1] = 0x00;
 
-    communication_service::write_report(communication_data, &report)
+    iowarrior_service::write_report(data, mut_data, &report)
 }
 
 fn send_disable_adc(
     data: &IOWarriorData,
-    communication_data: &mut CommunicationData,
-) -> Result<(), HidError> {
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+) -> Result<(), TransportError> {
     let mut report = data.create_report(Pipe::ADCMode);
 
     report.buffer[0] = ReportId::AdcSetup.get_value();
     report.buffer[1] = 0x00;
 
-    communication_service::write_report(communication_data, &report)
+    iowarrior_service::write_report(data, mut_data, &report)
 }
@@ -0,0 +1,14 @@
+use crate::iowarrior::{Peripheral, Report};
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PeripheralSetup {
+    pub peripheral: Peripheral,
+    pub reports: Vec<Report>,
+}
+
+impl fmt::Display for PeripheralSetup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
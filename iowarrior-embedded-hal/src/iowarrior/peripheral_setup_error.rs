@@ -1,12 +1,18 @@
+use crate::communication::TransportError;
 use crate::iowarrior::Peripheral;
-use hidapi::HidError;
 use thiserror::Error;
 
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum PeripheralSetupError {
-    #[error("USB HID error.")]
-    ErrorUSB(HidError),
+    #[error("USB transport error.")]
+    ErrorUSB(#[source] TransportError),
+    #[error("IOWarrior disconnected.")]
+    Disconnected,
+    #[error("Timeout while waiting for IOWarrior.")]
+    Timeout,
+    #[error("IOWarrior operation cancelled.")]
+    Cancelled,
     #[error("Hardware is already set up.")]
     AlreadySetup,
     #[error("Required hardware is blocked by other peripheral {0}.")]
@@ -16,3 +22,14 @@ pub enum PeripheralSetupError {
     #[error("Peripheral is not supported by hardware.")]
     NotSupported,
 }
+
+impl From<TransportError> for PeripheralSetupError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Disconnected => PeripheralSetupError::Disconnected,
+            TransportError::Timeout => PeripheralSetupError::Timeout,
+            TransportError::Cancelled => PeripheralSetupError::Cancelled,
+            error => PeripheralSetupError::ErrorUSB(error),
+        }
+    }
+}
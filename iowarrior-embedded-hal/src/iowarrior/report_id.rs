@@ -29,4 +29,24 @@ impl ReportId {
     pub fn get_value(&self) -> u8 {
         *self as u8
     }
+
+    pub fn from_u8(value: u8) -> Option<ReportId> {
+        match value {
+            0x1C => Some(ReportId::AdcSetup),
+            0x1D => Some(ReportId::AdcRead),
+            0x01 => Some(ReportId::I2cSetup),
+            0x02 => Some(ReportId::I2cWrite),
+            0x03 => Some(ReportId::I2cRead),
+            0x20 => Some(ReportId::PwmSetup),
+            0x21 => Some(ReportId::PwmParameters),
+            0x08 => Some(ReportId::SpiSetup),
+            0x09 => Some(ReportId::SpiTransfer),
+            0x28 => Some(ReportId::TimerSetup),
+            0x29 => Some(ReportId::TimerDataA),
+            0x2A => Some(ReportId::TimerDataB),
+            0x00 => Some(ReportId::GpioReadWrite),
+            0xFF => Some(ReportId::GpioSpecialRead),
+            _ => None,
+        }
+    }
 }
@@ -8,3 +8,5 @@ pub use self::pwm::*;
 pub use self::pwm_config::*;
 pub(crate) use self::pwm_data::*;
 pub use self::pwm_error::*;
+
+static_assertions::assert_impl_all!(PWM: Send, Sync);
@@ -1,18 +1,38 @@
+use crate::communication::TransportError;
 use embedded_hal::pwm::ErrorKind;
-use hidapi::HidError;
 use thiserror::Error;
 
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum PWMError {
-    #[error("USB HID error.")]
-    ErrorUSB(HidError),
+    #[error("USB transport error.")]
+    ErrorUSB(#[source] TransportError),
+    #[error("IOWarrior disconnected.")]
+    Disconnected,
+    #[error("Timeout while waiting for IOWarrior.")]
+    Timeout,
+    #[error("IOWarrior operation cancelled.")]
+    Cancelled,
+}
+
+impl From<TransportError> for PWMError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Disconnected => PWMError::Disconnected,
+            TransportError::Timeout => PWMError::Timeout,
+            TransportError::Cancelled => PWMError::Cancelled,
+            error => PWMError::ErrorUSB(error),
+        }
+    }
 }
 
 impl embedded_hal::pwm::Error for PWMError {
     fn kind(&self) -> ErrorKind {
         match self {
-            PWMError::ErrorUSB(_) => ErrorKind::Other,
+            PWMError::ErrorUSB(_)
+            | PWMError::Disconnected
+            | PWMError::Timeout
+            | PWMError::Cancelled => ErrorKind::Other,
         }
     }
 }
@@ -1,23 +1,22 @@
-use crate::communication::communication_service;
+use crate::communication::TransportError;
 use crate::iowarrior::{
-    peripheral_service, IOWarriorData, IOWarriorMutData, Peripheral, PeripheralSetupError, Pipe,
-    ReportId,
+    iowarrior_service, peripheral_service, IOWarriorData, IOWarriorMutData, Peripheral,
+    PeripheralSetupError, Pipe, ReportId,
 };
-use crate::pwm::{IOW56PWMConfig, IOWarriorPWMType, PWMChannel, PWMConfig, PWMData, PWMError, PWM};
+use crate::pwm::{IOW100PWMConfig, IOW56PWMConfig, IOWarriorPWMType};
+use crate::pwm::{PWMChannel, PWMConfig, PWMData, PWMError, PWM};
 use crate::{iowarrior::IOWarriorType, pin};
-use hidapi::HidError;
-use std::cell::{RefCell, RefMut};
-use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 pub fn new(
-    data: &Rc<IOWarriorData>,
-    mut_data_refcell: &Rc<RefCell<IOWarriorMutData>>,
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
     pwm_config: PWMConfig,
 ) -> Result<Vec<PWM>, PeripheralSetupError> {
     match get_pwm_type(&data, pwm_config) {
         None => Err(PeripheralSetupError::NotSupported),
         Some(pwm_type) => {
-            let mut mut_data = mut_data_refcell.borrow_mut();
+            let mut mut_data = mut_data_mutex.lock().unwrap();
 
             if pwm_type == IOWarriorPWMType::IOWarrior56
                 && pwm_config.iow56_config == IOW56PWMConfig::Two
@@ -36,18 +35,17 @@ pub fn new(
                 &pwm_pins,
             )?;
 
-            send_enable_pwm(&data, &mut mut_data, &pwm_data)
-                .map_err(|x| PeripheralSetupError::ErrorUSB(x))?;
+            send_enable_pwm(&data, &mut mut_data, &pwm_data).map_err(PeripheralSetupError::from)?;
 
             peripheral_service::post_enable(&mut mut_data, &pwm_pins, Peripheral::PWM);
 
-            let pwm_data_refcell = Rc::new(RefCell::new(pwm_data));
+            let pwm_data_mutex = Arc::new(Mutex::new(pwm_data));
 
             Ok((0..pwm_pins.len())
                 .map(|index| PWM {
                     data: data.clone(),
-                    mut_data_refcell: mut_data_refcell.clone(),
-                    pwm_data_refcell: pwm_data_refcell.clone(),
+                    mut_data_mutex: mut_data_mutex.clone(),
+                    pwm_data_mutex: pwm_data_mutex.clone(),
                     channel: PWMChannel::from_u8((index + 1) as u8),
                 })
                 .collect())
@@ -55,7 +53,7 @@ pub fn new(
     }
 }
 
-fn get_pwm_type(data: &Rc<IOWarriorData>, pwm_config: PWMConfig) -> Option<IOWarriorPWMType> {
+fn get_pwm_type(data: &Arc<IOWarriorData>, pwm_config: PWMConfig) -> Option<IOWarriorPWMType> {
     if data.device_type == IOWarriorType::IOWarrior100 {
         return Some(IOWarriorPWMType::IOWarrior100);
     }
@@ -78,6 +76,27 @@ fn get_pwm_type(data: &Rc<IOWarriorData>, pwm_config: PWMConfig) -> Option<IOWar
     return None;
 }
 
+pub fn get_alternate_pins(device_type: IOWarriorType) -> Vec<u8> {
+    let pwm_config = PWMConfig {
+        iow56_config: IOW56PWMConfig::Two,
+        iow100_config: IOW100PWMConfig::Four,
+        ..PWMConfig::default()
+    };
+
+    match device_type {
+        IOWarriorType::IOWarrior100 => get_pwm_pins(IOWarriorPWMType::IOWarrior100, pwm_config),
+        IOWarriorType::IOWarrior56 | IOWarriorType::IOWarrior56Dongle => {
+            get_pwm_pins(IOWarriorPWMType::IOWarrior56, pwm_config)
+        }
+        IOWarriorType::IOWarrior40
+        | IOWarriorType::IOWarrior24
+        | IOWarriorType::IOWarrior24PowerVampire
+        | IOWarriorType::IOWarrior28
+        | IOWarriorType::IOWarrior28Dongle
+        | IOWarriorType::IOWarrior28L => vec![],
+    }
+}
+
 fn get_pwm_pins(pwm_type: IOWarriorPWMType, pwm_config: PWMConfig) -> Vec<u8> {
     match pwm_type {
         IOWarriorPWMType::IOWarrior56 => [pin!(6, 7), pin!(6, 0)]
@@ -173,9 +192,11 @@ fn calculate_iow100_data(pwm_data: &mut PWMData) {
 
 fn send_enable_pwm(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     pwm_data: &PWMData,
-) -> Result<(), HidError> {
+) -> Result<(), TransportError> {
+    let mut reports = Vec::with_capacity(2);
+
     {
         let mut report = data.create_report(Pipe::SpecialMode);
 
@@ -190,7 +211,9 @@ fn send_enable_pwm(
             write_iow56_pwm_channel(&mut report.buffer[7..12], &pwm_data, PWMChannel::Second);
         }
 
-        communication_service::write_report(&mut mut_data.communication_data, &mut report)?;
+        iowarrior_service::write_report(data, mut_data, &report)?;
+
+        reports.push(report);
     }
 
     if pwm_data.pwm_type == IOWarriorPWMType::IOWarrior100 {
@@ -210,9 +233,13 @@ fn send_enable_pwm(
         write_iow100_pwm_channel(&mut report.buffer[10..12], &pwm_data, PWMChannel::Third);
         write_iow100_pwm_channel(&mut report.buffer[12..14], &pwm_data, PWMChannel::Fourth);
 
-        communication_service::write_report(&mut mut_data.communication_data, &mut report)?;
+        iowarrior_service::write_report(data, mut_data, &report)?;
+
+        reports.push(report);
     }
 
+    peripheral_service::store_peripheral_setup(mut_data, Peripheral::PWM, reports);
+
     Ok(())
 }
 
@@ -239,8 +266,8 @@ fn write_u16(bytes: &mut [u8], value: u16) {
 #[inline]
 pub fn update_duty_cycle(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     pwm_data: &PWMData,
 ) -> Result<(), PWMError> {
-    send_enable_pwm(data, mut_data, pwm_data).map_err(|x| PWMError::ErrorUSB(x))
+    send_enable_pwm(data, mut_data, pwm_data).map_err(PWMError::from)
 }
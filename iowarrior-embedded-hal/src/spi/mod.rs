@@ -8,3 +8,5 @@ pub use self::spi::*;
 pub use self::spi_config::*;
 pub(crate) use self::spi_data::*;
 pub use self::spi_error::*;
+
+static_assertions::assert_impl_all!(SPI: Send, Sync);
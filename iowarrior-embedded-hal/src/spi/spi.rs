@@ -1,15 +1,13 @@
 use crate::iowarrior::Peripheral;
 use crate::iowarrior::{peripheral_service, IOWarriorData, IOWarriorMutData};
 use crate::spi::{spi_service, SPIConfig, SPIData, SPIError};
-use std::cell::RefCell;
 use std::fmt;
-use std::rc::Rc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug)]
 pub struct SPI {
-    pub(crate) data: Rc<IOWarriorData>,
-    pub(crate) mut_data_refcell: Rc<RefCell<IOWarriorMutData>>,
+    pub(crate) data: Arc<IOWarriorData>,
+    pub(crate) mut_data_mutex: Arc<Mutex<IOWarriorMutData>>,
     pub(crate) spi_data: SPIData,
 }
 
@@ -24,7 +22,7 @@ impl Drop for SPI {
     fn drop(&mut self) {
         peripheral_service::disable_peripheral(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             Peripheral::SPI,
         );
     }
@@ -39,7 +37,7 @@ impl embedded_hal::spi::SpiBus<u8> for SPI {
     fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
         spi_service::read_data(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             words,
         )
@@ -49,7 +47,7 @@ impl embedded_hal::spi::SpiBus<u8> for SPI {
     fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
         spi_service::write_data(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             words,
         )
@@ -59,7 +57,7 @@ impl embedded_hal::spi::SpiBus<u8> for SPI {
     fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
         spi_service::transfer_data_with_different_size(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             read,
             write,
@@ -70,7 +68,7 @@ impl embedded_hal::spi::SpiBus<u8> for SPI {
     fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
         spi_service::transfer_data_in_place(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             words,
         )
@@ -92,7 +90,7 @@ impl embedded_hal_0::blocking::spi::Transfer<u8> for SPI {
 
         spi_service::transfer_data_with_same_size(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             buffer,
             write_buffer.as_slice(),
@@ -110,7 +108,7 @@ impl embedded_hal_0::blocking::spi::Write<u8> for SPI {
     fn write(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
         spi_service::write_data(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             buffer,
         )
@@ -130,7 +128,7 @@ impl embedded_hal_0::blocking::spi::WriteIter<u8> for SPI {
 
         spi_service::write_data(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             write.as_slice(),
         )
@@ -146,7 +144,7 @@ impl embedded_hal_0::blocking::spi::Transactional<u8> for SPI {
         &mut self,
         operations: &mut [embedded_hal_0::blocking::spi::Operation<'a, u8>],
     ) -> Result<(), Self::Error> {
-        let mut mut_data = self.mut_data_refcell.borrow_mut();
+        let mut mut_data = self.mut_data_mutex.lock().unwrap();
 
         for operation in operations {
             match operation {
@@ -173,47 +171,19 @@ impl embedded_hal::spi::SpiDevice for SPI {
         &mut self,
         operations: &mut [embedded_hal::spi::Operation<'_, u8>],
     ) -> Result<(), Self::Error> {
-        let mut mut_data = self.mut_data_refcell.borrow_mut();
-
-        for operation in operations {
-            match operation {
-                embedded_hal::spi::Operation::Read(read) => {
-                    spi_service::read_data(&self.data, &mut mut_data, &self.spi_data, read)?;
-                }
-                embedded_hal::spi::Operation::Write(write) => {
-                    spi_service::write_data(&self.data, &mut mut_data, &self.spi_data, write)?;
-                }
-                embedded_hal::spi::Operation::Transfer(read, write) => {
-                    spi_service::transfer_data_with_different_size(
-                        &self.data,
-                        &mut mut_data,
-                        &self.spi_data,
-                        read,
-                        write,
-                    )?;
-                }
-                embedded_hal::spi::Operation::TransferInPlace(buf) => {
-                    spi_service::transfer_data_in_place(
-                        &self.data,
-                        &mut mut_data,
-                        &self.spi_data,
-                        buf,
-                    )?;
-                }
-                embedded_hal::spi::Operation::DelayNs(delay_ns) => {
-                    std::thread::sleep(Duration::from_nanos(delay_ns.clone() as u64));
-                }
-            }
-        }
-
-        Ok(())
+        spi_service::transaction(
+            &self.data,
+            &mut self.mut_data_mutex.lock().unwrap(),
+            &self.spi_data,
+            operations,
+        )
     }
 
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
         spi_service::read_data(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             buf,
         )
@@ -223,7 +193,7 @@ impl embedded_hal::spi::SpiDevice for SPI {
     fn write(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
         spi_service::write_data(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             buf,
         )
@@ -233,7 +203,7 @@ impl embedded_hal::spi::SpiDevice for SPI {
     fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
         spi_service::transfer_data_with_different_size(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             read,
             write,
@@ -244,13 +214,68 @@ impl embedded_hal::spi::SpiDevice for SPI {
     fn transfer_in_place(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
         spi_service::transfer_data_in_place(
             &self.data,
-            &mut self.mut_data_refcell.borrow_mut(),
+            &mut self.mut_data_mutex.lock().unwrap(),
             &self.spi_data,
             buf,
         )
     }
 }
 
+#[cfg(feature = "embedded-hal-async")]
+impl embedded_hal_async::spi::SpiBus<u8> for SPI {
+    #[inline]
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.transaction_async(&mut [embedded_hal_async::spi::Operation::Read(words)])
+            .await
+    }
+
+    #[inline]
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.transaction_async(&mut [embedded_hal_async::spi::Operation::Write(words)])
+            .await
+    }
+
+    #[inline]
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.transaction_async(&mut [embedded_hal_async::spi::Operation::Transfer(read, write)])
+            .await
+    }
+
+    #[inline]
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.transaction_async(&mut [embedded_hal_async::spi::Operation::TransferInPlace(words)])
+            .await
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "embedded-hal-async")]
+impl embedded_hal_async::spi::SpiDevice for SPI {
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [embedded_hal_async::spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        self.transaction_async(operations).await
+    }
+}
+
+#[cfg(feature = "embedded-hal-async")]
+impl SPI {
+    #[inline]
+    async fn transaction_async(
+        &mut self,
+        operations: &mut [embedded_hal_async::spi::Operation<'_, u8>],
+    ) -> Result<(), SPIError> {
+        spi_service::transaction_async(&self.data, &self.mut_data_mutex, &self.spi_data, operations)
+            .await
+    }
+}
+
 impl SPI {
     #[inline]
     pub fn get_config(&self) -> SPIConfig {
@@ -1,19 +1,40 @@
-use hidapi::HidError;
+use crate::communication::TransportError;
 use thiserror::Error;
 
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum SPIError {
-    #[error("USB HID error.")]
-    ErrorUSB(HidError),
+    #[error("USB transport error.")]
+    ErrorUSB(#[source] TransportError),
+    #[error("IOWarrior disconnected.")]
+    Disconnected,
+    #[error("Timeout while waiting for IOWarrior.")]
+    Timeout,
+    #[error("IOWarrior operation cancelled.")]
+    Cancelled,
     #[error("SPI input output error.")]
     IOErrorSPI,
 }
 
+impl From<TransportError> for SPIError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Disconnected => SPIError::Disconnected,
+            TransportError::Timeout => SPIError::Timeout,
+            TransportError::Cancelled => SPIError::Cancelled,
+            error => SPIError::ErrorUSB(error),
+        }
+    }
+}
+
 impl embedded_hal::spi::Error for SPIError {
     fn kind(&self) -> embedded_hal::spi::ErrorKind {
         match self {
-            SPIError::ErrorUSB(_) | SPIError::IOErrorSPI => embedded_hal::spi::ErrorKind::Other,
+            SPIError::ErrorUSB(_)
+            | SPIError::Disconnected
+            | SPIError::Timeout
+            | SPIError::Cancelled
+            | SPIError::IOErrorSPI => embedded_hal::spi::ErrorKind::Other,
         }
     }
 }
@@ -1,28 +1,30 @@
 use crate::bits::Bit::{Bit1, Bit2, Bit3, Bit6, Bit7};
 use crate::bits::Bitmasking;
-use crate::communication::communication_service;
+#[cfg(feature = "embedded-hal-async")]
+use crate::communication::blocking_service;
+use crate::communication::TransportError;
 use crate::iowarrior::{
-    peripheral_service, IOWarriorData, IOWarriorMutData, Peripheral, PeripheralSetupError, Pipe,
-    Report, ReportId,
+    iowarrior_service, peripheral_service, IOWarriorData, IOWarriorMutData, Peripheral,
+    PeripheralSetupError, Pipe, Report, ReportId,
 };
 use crate::spi::spi_data::{IOWarriorSPIType, SPIData};
 use crate::spi::{SPIConfig, SPIError, SPIMode, SPI};
 use crate::{iowarrior::IOWarriorType, pin};
-use hidapi::HidError;
-use std::cell::{RefCell, RefMut};
+use embedded_hal::spi::Operation;
 use std::cmp::Ordering;
 use std::iter;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
 pub fn new(
-    data: &Rc<IOWarriorData>,
-    mut_data_refcell: &Rc<RefCell<IOWarriorMutData>>,
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
     spi_config: SPIConfig,
 ) -> Result<SPI, PeripheralSetupError> {
-    match get_spi_type(&data) {
+    match get_spi_type(data.device_type) {
         None => Err(PeripheralSetupError::NotSupported),
         Some(spi_type) => {
-            let mut mut_data = mut_data_refcell.borrow_mut();
+            let mut mut_data = mut_data_mutex.lock().unwrap();
 
             if spi_type == IOWarriorSPIType::IOWarrior56
                 && peripheral_service::get_used_pins(&mut mut_data, Peripheral::PWM).len() > 1
@@ -40,22 +42,28 @@ pub fn new(
                 &spi_pins,
             )?;
 
-            send_enable_spi(&data, &mut mut_data, &spi_data)
-                .map_err(|x| PeripheralSetupError::ErrorUSB(x))?;
+            send_enable_spi(&data, &mut mut_data, &spi_data).map_err(PeripheralSetupError::from)?;
 
             peripheral_service::post_enable(&mut mut_data, &spi_pins, Peripheral::SPI);
 
             Ok(SPI {
                 data: data.clone(),
-                mut_data_refcell: mut_data_refcell.clone(),
+                mut_data_mutex: mut_data_mutex.clone(),
                 spi_data,
             })
         }
     }
 }
 
-fn get_spi_type(data: &Rc<IOWarriorData>) -> Option<IOWarriorSPIType> {
-    match data.device_type {
+pub fn get_alternate_pins(device_type: IOWarriorType) -> Vec<u8> {
+    match get_spi_type(device_type) {
+        None => vec![],
+        Some(spi_type) => get_spi_pins(spi_type),
+    }
+}
+
+fn get_spi_type(device_type: IOWarriorType) -> Option<IOWarriorSPIType> {
+    match device_type {
         IOWarriorType::IOWarrior24 | IOWarriorType::IOWarrior24PowerVampire => {
             Some(IOWarriorSPIType::IOWarrior24)
         }
@@ -134,9 +142,9 @@ fn calculate_iow56_data(spi_data: &mut SPIData) {
 
 fn send_enable_spi(
     data: &IOWarriorData,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     spi_data: &SPIData,
-) -> Result<(), HidError> {
+) -> Result<(), TransportError> {
     let mut report = data.create_report(Pipe::SpecialMode);
 
     report.buffer[0] = ReportId::SpiSetup.get_value();
@@ -196,12 +204,126 @@ fn send_enable_spi(
         }
     }
 
-    communication_service::write_report(&mut mut_data.communication_data, &mut report)
+    iowarrior_service::write_report(data, mut_data, &report)?;
+
+    peripheral_service::store_peripheral_setup(mut_data, Peripheral::SPI, vec![report]);
+
+    Ok(())
+}
+
+pub fn transaction(
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
+    spi_data: &SPIData,
+    operations: &mut [Operation<'_, u8>],
+) -> Result<(), SPIError> {
+    for operation in operations {
+        match operation {
+            Operation::Read(read) => {
+                read_data(data, mut_data, spi_data, read)?;
+            }
+            Operation::Write(write) => {
+                write_data(data, mut_data, spi_data, write)?;
+            }
+            Operation::Transfer(read, write) => {
+                transfer_data_with_different_size(data, mut_data, spi_data, read, write)?;
+            }
+            Operation::TransferInPlace(buf) => {
+                transfer_data_in_place(data, mut_data, spi_data, buf)?;
+            }
+            Operation::DelayNs(delay_ns) => {
+                std::thread::sleep(Duration::from_nanos(*delay_ns as u64));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "embedded-hal-async")]
+pub async fn transaction_async(
+    data: &Arc<IOWarriorData>,
+    mut_data_mutex: &Arc<Mutex<IOWarriorMutData>>,
+    spi_data: &SPIData,
+    operations: &mut [Operation<'_, u8>],
+) -> Result<(), SPIError> {
+    let data = data.clone();
+    let mut_data_mutex = mut_data_mutex.clone();
+    let spi_data = *spi_data;
+
+    let mut owned_operations: Vec<OwnedOperation> = operations
+        .iter()
+        .map(|operation| match operation {
+            Operation::Read(read) => OwnedOperation::Read(read.to_vec()),
+            Operation::Write(write) => OwnedOperation::Write(write.to_vec()),
+            Operation::Transfer(read, write) => {
+                OwnedOperation::Transfer(read.to_vec(), write.to_vec())
+            }
+            Operation::TransferInPlace(buf) => OwnedOperation::TransferInPlace(buf.to_vec()),
+            Operation::DelayNs(delay_ns) => OwnedOperation::DelayNs(*delay_ns),
+        })
+        .collect();
+
+    let (result, owned_operations) = blocking_service::spawn_blocking(move |cancelled| {
+        let result = (|| {
+            let mut mut_data = mut_data_mutex.lock().unwrap();
+
+            for operation in owned_operations.iter_mut() {
+                // A dropped future stops the transaction before the next operation.
+                if cancelled.load(std::sync::atomic::Ordering::Relaxed) {
+                    return Err(SPIError::Cancelled);
+                }
+
+                let mut borrowed_operation = match operation {
+                    OwnedOperation::Read(read) => Operation::Read(read),
+                    OwnedOperation::Write(write) => Operation::Write(write),
+                    OwnedOperation::Transfer(read, write) => Operation::Transfer(read, write),
+                    OwnedOperation::TransferInPlace(buf) => Operation::TransferInPlace(buf),
+                    OwnedOperation::DelayNs(delay_ns) => Operation::DelayNs(*delay_ns),
+                };
+
+                transaction(
+                    &data,
+                    &mut mut_data,
+                    &spi_data,
+                    std::slice::from_mut(&mut borrowed_operation),
+                )?;
+            }
+
+            Ok(())
+        })();
+
+        (result, owned_operations)
+    })
+    .map_err(SPIError::from)?
+    .await;
+
+    for (operation, owned_operation) in operations.iter_mut().zip(owned_operations) {
+        match (operation, owned_operation) {
+            (Operation::Read(read), OwnedOperation::Read(buffer))
+            | (Operation::Transfer(read, _), OwnedOperation::Transfer(buffer, _))
+            | (Operation::TransferInPlace(read), OwnedOperation::TransferInPlace(buffer)) => {
+                read.copy_from_slice(&buffer);
+            }
+            _ => {}
+        }
+    }
+
+    result
+}
+
+#[cfg(feature = "embedded-hal-async")]
+enum OwnedOperation {
+    Read(Vec<u8>),
+    Write(Vec<u8>),
+    Transfer(Vec<u8>, Vec<u8>),
+    TransferInPlace(Vec<u8>),
+    DelayNs(u32),
 }
 
 pub fn read_data(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     spi_data: &SPIData,
     words: &mut [u8],
 ) -> Result<(), SPIError> {
@@ -232,8 +354,8 @@ pub fn read_data(
 }
 
 pub fn write_data(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     spi_data: &SPIData,
     words: &[u8],
 ) -> Result<(), SPIError> {
@@ -269,8 +391,8 @@ pub fn write_data(
 }
 
 pub fn transfer_data_with_different_size(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     spi_data: &SPIData,
     read: &mut [u8],
     write: &[u8],
@@ -310,8 +432,8 @@ pub fn transfer_data_with_different_size(
 }
 
 pub fn transfer_data_with_same_size(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     spi_data: &SPIData,
     read: &mut [u8],
     write: &[u8],
@@ -342,8 +464,8 @@ pub fn transfer_data_with_same_size(
 }
 
 pub fn transfer_data_in_place(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     spi_data: &SPIData,
     words: &mut [u8],
 ) -> Result<(), SPIError> {
@@ -371,7 +493,7 @@ pub fn transfer_data_in_place(
     Ok(())
 }
 
-fn get_chunk_size(data: &Rc<IOWarriorData>, spi_data: &SPIData) -> usize {
+fn get_chunk_size(data: &Arc<IOWarriorData>, spi_data: &SPIData) -> usize {
     data.special_report_size
         - match spi_data.spi_type {
             IOWarriorSPIType::IOWarrior24 => 2usize,
@@ -380,8 +502,8 @@ fn get_chunk_size(data: &Rc<IOWarriorData>, spi_data: &SPIData) -> usize {
 }
 
 fn write_report(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     spi_data: &SPIData,
     write_chunk: &[u8],
     use_data_ready_pin: bool,
@@ -424,27 +546,26 @@ fn write_report(
         .buffer
         .extend(iter::repeat(0u8).take(data.special_report_size - report.buffer.len()));
 
-    communication_service::write_report(&mut mut_data.communication_data, &report)
-        .map_err(|x| SPIError::ErrorUSB(x))
+    iowarrior_service::write_report(data, mut_data, &report).map_err(SPIError::from)
 }
 
 fn read_report(
-    data: &Rc<IOWarriorData>,
-    mut_data: &mut RefMut<IOWarriorMutData>,
+    data: &Arc<IOWarriorData>,
+    mut_data: &mut MutexGuard<IOWarriorMutData>,
     spi_data: &SPIData,
     read_chunk: &mut [u8],
 ) -> Result<(), SPIError> {
-    let report = communication_service::read_report(
-        &mut mut_data.communication_data,
-        data.create_report(Pipe::SpecialMode),
-    )
-    .map_err(|x| SPIError::ErrorUSB(x))?;
+    let report =
+        iowarrior_service::read_report(data, mut_data, data.create_report(Pipe::SpecialMode))
+            .map_err(SPIError::from)?;
 
     assert_eq!(report.buffer[0], ReportId::SpiTransfer.get_value());
 
+    // The SPI input report ($09) in the IOWarrior24 and IOWarrior56 datasheets holds the report
+    // ID in byte 0, the number of bytes read in byte 1 and the data from byte 2 on.
     match read_chunk.len() as u8 == report.buffer[1] {
         true => {
-            read_chunk.copy_from_slice(&report.buffer[1..(read_chunk.len() + 1)]);
+            read_chunk.copy_from_slice(&report.buffer[2..(read_chunk.len() + 2)]);
             Ok(())
         }
         false => Err(SPIError::IOErrorSPI),
@@ -0,0 +1,172 @@
+use crate::{IowkitPipe, IowkitSession, DWORD, PCHAR, ULONG};
+use std::fmt;
+use std::os::raw;
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::time::Duration;
+
+const IOWKIT_INFINITE: ULONG = 0xFFFFFFFF;
+const SERIAL_NUMBER_LENGTH: usize = 9;
+
+#[derive(Debug, Clone)]
+pub struct IowkitDevice {
+    pub(crate) session: Arc<IowkitSession>,
+    pub(crate) device_number: ULONG,
+    pub(crate) device_handle: NonNull<raw::c_void>,
+}
+
+impl fmt::Display for IowkitDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// See `IowkitSession`, the session kept by the device keeps the handle valid.
+unsafe impl Send for IowkitDevice {}
+unsafe impl Sync for IowkitDevice {}
+
+impl IowkitDevice {
+    pub fn get_session(&self) -> &Arc<IowkitSession> {
+        &self.session
+    }
+
+    pub fn get_device_number(&self) -> ULONG {
+        self.device_number
+    }
+
+    pub fn get_product_id(&self) -> u16 {
+        let result = unsafe {
+            self.session
+                .library
+                .get_iowkit()
+                .IowKitGetProductId(self.device_handle.as_ptr())
+        };
+
+        result as u16
+    }
+
+    pub fn get_revision(&self) -> u16 {
+        let result = unsafe {
+            self.session
+                .library
+                .get_iowkit()
+                .IowKitGetRevision(self.device_handle.as_ptr())
+        };
+
+        result as u16
+    }
+
+    pub fn get_serial_number(&self) -> Option<String> {
+        let mut raw_serial_number = [0u16; SERIAL_NUMBER_LENGTH];
+
+        let result = unsafe {
+            self.session
+                .library
+                .get_iowkit()
+                .IowKitGetSerialNumber(self.device_handle.as_ptr(), raw_serial_number.as_mut_ptr())
+        };
+
+        if result == 0 {
+            return None;
+        }
+
+        let length = raw_serial_number
+            .iter()
+            .position(|x| *x == 0)
+            .unwrap_or(raw_serial_number.len());
+
+        Some(String::from_utf16_lossy(&raw_serial_number[..length]))
+    }
+
+    pub fn write(&self, pipe: IowkitPipe, buffer: &[u8]) -> usize {
+        let result = unsafe {
+            self.session.library.get_iowkit().IowKitWrite(
+                self.device_handle.as_ptr(),
+                pipe.get_value(),
+                buffer.as_ptr() as PCHAR,
+                buffer.len() as ULONG,
+            )
+        };
+
+        result as usize
+    }
+
+    pub fn read(&self, pipe: IowkitPipe, buffer: &mut [u8]) -> usize {
+        let result = unsafe {
+            self.session.library.get_iowkit().IowKitRead(
+                self.device_handle.as_ptr(),
+                pipe.get_value(),
+                buffer.as_mut_ptr() as PCHAR,
+                buffer.len() as ULONG,
+            )
+        };
+
+        result as usize
+    }
+
+    pub fn read_non_blocking(&self, pipe: IowkitPipe, buffer: &mut [u8]) -> usize {
+        let result = unsafe {
+            self.session.library.get_iowkit().IowKitReadNonBlocking(
+                self.device_handle.as_ptr(),
+                pipe.get_value(),
+                buffer.as_mut_ptr() as PCHAR,
+                buffer.len() as ULONG,
+            )
+        };
+
+        result as usize
+    }
+
+    pub fn read_immediate(&self) -> Option<u32> {
+        let mut value: DWORD = 0;
+
+        let result = unsafe {
+            self.session
+                .library
+                .get_iowkit()
+                .IowKitReadImmediate(self.device_handle.as_ptr(), &mut value)
+        };
+
+        match result {
+            0 => None,
+            _ => Some(value as u32),
+        }
+    }
+
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> bool {
+        unsafe {
+            self.session
+                .library
+                .get_iowkit()
+                .IowKitSetTimeout(self.device_handle.as_ptr(), to_iowkit_timeout(timeout))
+                != 0
+        }
+    }
+
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> bool {
+        unsafe {
+            self.session
+                .library
+                .get_iowkit()
+                .IowKitSetWriteTimeout(self.device_handle.as_ptr(), to_iowkit_timeout(timeout))
+                != 0
+        }
+    }
+
+    pub fn cancel_io(&self, pipe: IowkitPipe) -> bool {
+        unsafe {
+            self.session
+                .library
+                .get_iowkit()
+                .IowKitCancelIo(self.device_handle.as_ptr(), pipe.get_value())
+                != 0
+        }
+    }
+}
+
+fn to_iowkit_timeout(timeout: Option<Duration>) -> ULONG {
+    match timeout {
+        None => IOWKIT_INFINITE,
+        Some(timeout) => timeout.as_millis().clamp(1, (IOWKIT_INFINITE - 1) as u128) as ULONG,
+    }
+}
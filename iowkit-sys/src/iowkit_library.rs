@@ -0,0 +1,86 @@
+use crate::{iowkit_session, Iowkit, IowkitSession, IOW_OPEN_COMPLEX, IOW_OPEN_SIMPLE, ULONG};
+use std::ffi::{CStr, OsStr};
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LegacyOpenMode {
+    Simple,
+    Complex,
+}
+
+impl LegacyOpenMode {
+    pub const fn get_value(&self) -> ULONG {
+        (match self {
+            LegacyOpenMode::Simple => IOW_OPEN_SIMPLE,
+            LegacyOpenMode::Complex => IOW_OPEN_COMPLEX,
+        }) as ULONG
+    }
+}
+
+impl fmt::Display for LegacyOpenMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug)]
+pub struct IowkitLibrary {
+    iowkit: Iowkit,
+    legacy_open_mode: LegacyOpenMode,
+}
+
+impl fmt::Display for IowkitLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl IowkitLibrary {
+    // Loading a shared library runs its initialization code, the caller has to make sure that
+    // `path` points to an iowkit library.
+    pub unsafe fn new<P: AsRef<OsStr>>(
+        path: P,
+        legacy_open_mode: LegacyOpenMode,
+    ) -> Result<Arc<IowkitLibrary>, libloading::Error> {
+        Ok(IowkitLibrary::from_iowkit(
+            Iowkit::new(path)?,
+            legacy_open_mode,
+        ))
+    }
+
+    pub fn from_iowkit(iowkit: Iowkit, legacy_open_mode: LegacyOpenMode) -> Arc<IowkitLibrary> {
+        Arc::new(IowkitLibrary {
+            iowkit,
+            legacy_open_mode,
+        })
+    }
+
+    pub fn get_iowkit(&self) -> &Iowkit {
+        &self.iowkit
+    }
+
+    pub fn get_version(&self) -> &str {
+        if self.iowkit.IowKitVersion.is_err() {
+            return "";
+        }
+
+        let version = unsafe { self.iowkit.IowKitVersion() };
+
+        match version.is_null() {
+            true => "",
+            false => unsafe { CStr::from_ptr(version) }.to_str().unwrap_or(""),
+        }
+    }
+
+    pub fn get_legacy_open_mode(&self) -> LegacyOpenMode {
+        self.legacy_open_mode
+    }
+
+    // Returns the already open session of the process if there is one, devices attached after it
+    // was opened show up once every session is dropped. The legacy open mode is only applied when
+    // a new session is opened.
+    pub fn open(self: &Arc<Self>) -> Option<Arc<IowkitSession>> {
+        iowkit_session::open_session(self)
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{IOW_PIPE_ADC_MODE, IOW_PIPE_I2C_MODE, IOW_PIPE_IO_PINS, IOW_PIPE_SPECIAL_MODE, ULONG};
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum IowkitPipe {
+    IOPins,
+    SpecialMode,
+    I2CMode,
+    ADCMode,
+}
+
+impl IowkitPipe {
+    pub const fn get_value(&self) -> ULONG {
+        (match self {
+            IowkitPipe::IOPins => IOW_PIPE_IO_PINS,
+            IowkitPipe::SpecialMode => IOW_PIPE_SPECIAL_MODE,
+            IowkitPipe::I2CMode => IOW_PIPE_I2C_MODE,
+            IowkitPipe::ADCMode => IOW_PIPE_ADC_MODE,
+        }) as ULONG
+    }
+
+    pub const fn from_value(value: ULONG) -> Option<IowkitPipe> {
+        match value as u32 {
+            IOW_PIPE_IO_PINS => Some(IowkitPipe::IOPins),
+            IOW_PIPE_SPECIAL_MODE => Some(IowkitPipe::SpecialMode),
+            IOW_PIPE_I2C_MODE => Some(IowkitPipe::I2CMode),
+            IOW_PIPE_ADC_MODE => Some(IowkitPipe::ADCMode),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IowkitPipe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
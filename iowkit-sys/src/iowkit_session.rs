@@ -0,0 +1,127 @@
+use crate::{IowkitDevice, IowkitLibrary, ULONG};
+use std::fmt;
+use std::os::raw;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex, PoisonError};
+
+// `IowKitOpenDevice` opens every IOWarrior of the process and `IowKitCloseDevice` closes all of
+// them, so all sessions share one set of handles which is only closed with the last session.
+static SHARED_SESSION: Mutex<Option<SharedSession>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct SharedSession {
+    library: Arc<IowkitLibrary>,
+    session_handle: NonNull<raw::c_void>,
+    session_count: usize,
+}
+
+// The handle is only opened and closed while `SHARED_SESSION` is locked.
+unsafe impl Send for SharedSession {}
+
+#[derive(Debug)]
+pub struct IowkitSession {
+    pub(crate) library: Arc<IowkitLibrary>,
+    pub(crate) session_handle: NonNull<raw::c_void>,
+}
+
+impl fmt::Display for IowkitSession {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// The handles stay valid until the last session is dropped. iowkit keeps no per-thread state for
+// a handle and `IowKitCancelIo` is meant to be called while another thread blocks in `IowKitRead`,
+// so the handles may be used from any thread.
+unsafe impl Send for IowkitSession {}
+unsafe impl Sync for IowkitSession {}
+
+impl Drop for IowkitSession {
+    #[inline]
+    fn drop(&mut self) {
+        let mut shared_session = SHARED_SESSION
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let session_count = match shared_session.as_mut() {
+            None => return,
+            Some(x) => {
+                x.session_count -= 1;
+                x.session_count
+            }
+        };
+
+        if session_count == 0 {
+            unsafe {
+                self.library
+                    .get_iowkit()
+                    .IowKitCloseDevice(self.session_handle.as_ptr())
+            }
+
+            *shared_session = None;
+        }
+    }
+}
+
+pub(crate) fn open_session(library: &Arc<IowkitLibrary>) -> Option<Arc<IowkitSession>> {
+    let mut shared_session = SHARED_SESSION
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    if shared_session.is_none() {
+        let iowkit = library.get_iowkit();
+
+        unsafe { iowkit.IowKitSetLegacyOpenMode(library.get_legacy_open_mode().get_value()) };
+
+        let session_handle = NonNull::new(unsafe { iowkit.IowKitOpenDevice() })?;
+
+        *shared_session = Some(SharedSession {
+            library: library.clone(),
+            session_handle,
+            session_count: 0,
+        });
+    }
+
+    let shared_session = shared_session.as_mut()?;
+
+    shared_session.session_count += 1;
+
+    Some(Arc::new(IowkitSession {
+        library: shared_session.library.clone(),
+        session_handle: shared_session.session_handle,
+    }))
+}
+
+impl IowkitSession {
+    pub fn get_library(&self) -> &Arc<IowkitLibrary> {
+        &self.library
+    }
+
+    pub fn get_device_count(&self) -> ULONG {
+        unsafe { self.library.get_iowkit().IowKitGetNumDevs() }
+    }
+
+    pub fn get_device(self: &Arc<Self>, device_number: ULONG) -> Option<IowkitDevice> {
+        if device_number == 0 || device_number > self.get_device_count() {
+            return None;
+        }
+
+        let device_handle = NonNull::new(unsafe {
+            self.library
+                .get_iowkit()
+                .IowKitGetDeviceHandle(device_number)
+        })?;
+
+        Some(IowkitDevice {
+            session: self.clone(),
+            device_number,
+            device_handle,
+        })
+    }
+
+    pub fn get_devices(self: &Arc<Self>) -> Vec<IowkitDevice> {
+        (1..=self.get_device_count())
+            .filter_map(|device_number| self.get_device(device_number))
+            .collect()
+    }
+}
@@ -13,4 +13,13 @@
 // warnings and according to commentary are not useful. See
 // https://github.com/rust-lang/rust-bindgen/issues/1651 for more.
 mod bindings;
+mod iowkit_device;
+mod iowkit_library;
+mod iowkit_pipe;
+mod iowkit_session;
+
 pub use self::bindings::*;
+pub use self::iowkit_device::*;
+pub use self::iowkit_library::*;
+pub use self::iowkit_pipe::*;
+pub use self::iowkit_session::*;